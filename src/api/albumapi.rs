@@ -1,164 +1,251 @@
 #![allow(clippy::future_not_send)]
 
-use crate::models::{
-    album::Album,
-    picture::Picture,
-    vars::{AUTH_HEAD, BASE_URI, DESTROY_URI, UPLOAD_URI},
+use crate::{
+    api::{
+        backend::{self, OnProgress, PictureSize},
+        error::ApiError,
+    },
+    models::{
+        album::Album, album_filter::AlbumFilter, album_summary::AlbumSummary, group::Group,
+        paged::Paged, picture::Picture, session,
+    },
 };
-use gloo_net::http::{Method, Request};
-use hex::ToHex;
-use load_dotenv::load_dotenv;
-use ring::digest;
 use seed::prelude::*;
-use web_sys::FormData;
+use uuid::Uuid;
+use web_sys::{AbortSignal, FormData};
 
-pub async fn get_my_ablums(auth: String) -> Option<Vec<Album>> {
-    let uri = BASE_URI.to_string() + "myalbums";
-    let response = Request::get(&uri)
-        .header(AUTH_HEAD, &auth)
-        .send()
+pub async fn get_used_public_ids() -> Result<Vec<String>, ApiError> {
+    backend::albums()
+        .get_used_public_ids(session::auth_header())
+        .await
+}
+
+pub async fn get_album_summaries(
+    filter: AlbumFilter,
+    cursor: Option<String>,
+) -> Result<Paged<AlbumSummary>, ApiError> {
+    backend::albums()
+        .get_album_summaries(filter, cursor, session::auth_header())
         .await
-        .expect("HTTP request failed");
-
-    match response.status() {
-        200 => {
-            let albums = response
-                .json::<Vec<Album>>()
-                .await
-                .expect("deserialization failed");
-            Some(albums)
-        }
-        _ => None,
-    }
 }
 
-pub async fn get_album(
-    id: Option<String>,
-    share_id: Option<String>,
-    auth: String,
-) -> Option<Album> {
+pub async fn get_album(id: Option<String>, share_id: Option<String>) -> Result<Album, ApiError> {
     let id = id.unwrap_or_default();
     let share_id = share_id.unwrap_or_default();
-    let uri = format!("{BASE_URI}getalbum?id={id}&share_id={share_id}");
-    let response = Request::get(&uri)
-        .header(AUTH_HEAD, &auth)
-        .send()
+    backend::albums()
+        .get_album(id, share_id, session::auth_header())
         .await
-        .expect("HTTP request failed");
-
-    match response.status() {
-        200 => {
-            let album = response
-                .json::<Album>()
-                .await
-                .expect("deserialization failed");
-            Some(album)
-        }
-        _ => None,
-    }
 }
 
-pub async fn update_album(album: Album, auth: String) -> Option<String> {
-    let mut res = None;
-    let uri = BASE_URI.to_string() + "editalbum";
-    let response = Request::new(&uri)
-        .method(Method::PUT)
-        .header(AUTH_HEAD, &auth)
-        .json(&album)
-        .expect("Serialization failed")
-        .send()
+// The stored album is returned, with its id and new version
+pub async fn update_album(album: Album) -> Result<Album, ApiError> {
+    backend::albums()
+        .update_album(album, session::auth_header())
         .await
-        .expect("HTTP request failed");
+}
 
-    if response.status() == 200 {
-        let res_id = response.json::<String>().await;
-        if let Ok(id) = res_id {
-            res = Some(id);
-        }
+// The pictures are copied into the folder of the new album, deleting one album keeps the other intact
+pub async fn duplicate_album(id: String) -> Result<String, ApiError> {
+    let mut album = get_album(Some(id), None).await?.duplicate();
+    // Saved without pictures first to get the id of its folder
+    let pictures: Vec<Vec<Picture>> = album
+        .groups
+        .iter_mut()
+        .flatten()
+        .map(|group| std::mem::take(group.pictures.get_or_insert_with(Vec::new)))
+        .collect();
+    let stored = update_album(album.clone()).await?;
+    album.id = stored.id;
+    album.version = stored.version;
+
+    let res = async {
+        copy_pictures(&mut album, pictures).await?;
+        update_album(album.clone()).await
     }
-    res
+    .await;
+    if res.is_err() {
+        // The copies made so far and the new album are removed
+        delete_pictures(album.public_ids()).await;
+        _ = delete_ablum(album.id.clone()).await;
+    }
+    res.map(|stored| stored.id)
 }
 
-pub async fn delete_ablum(id: String, auth: String) -> bool {
-    let delete_uri = format!("{BASE_URI}deletealbum?id={id}");
-    let delete_response = Request::new(&delete_uri)
-        .header(AUTH_HEAD, &auth)
-        .method(Method::DELETE)
-        .send()
-        .await
-        .expect("HTTP request failed");
-    delete_response.status() == 204
+// The copies are added to the groups, in the folder of the album
+async fn copy_pictures(album: &mut Album, pictures: Vec<Vec<Picture>>) -> Result<(), ApiError> {
+    let folder = format!("amaker/{}", album.id);
+    for (group, pictures) in album.groups.iter_mut().flatten().zip(pictures) {
+        for picture in pictures {
+            let is_cover = album.cover.as_ref() == Some(&picture.asset_id);
+            let copy = backend::media()
+                .copy_picture(picture, folder.clone())
+                .await?;
+            if is_cover {
+                album.cover = Some(copy.asset_id.clone());
+            }
+            group.pictures.get_or_insert_with(Vec::new).push(copy);
+        }
+    }
+    Ok(())
 }
 
-pub async fn preload_picture(uri: String) -> bool {
-    let response = Request::new(&uri)
-        .method(Method::GET)
-        .send()
-        .await
-        .expect("HTTP request failed");
-
-    if response.status() == 200 {
-        return true;
+// The groups of the source are appended to the target, then the source is deleted
+pub async fn merge_albums(source_id: String, target_id: String) -> Result<(), ApiError> {
+    let source = get_album(Some(source_id.clone()), None).await?;
+    let mut target = get_album(Some(target_id), None).await?;
+    let mut groups = source.groups.unwrap_or_default();
+    let mut copies = Vec::new();
+    let res = async {
+        let moved = rehome(&mut groups, &format!("amaker/{}", target.id), &mut copies).await?;
+        target.groups.get_or_insert_with(Vec::new).extend(groups);
+        update_album(target).await?;
+        Ok::<_, ApiError>(moved)
     }
-    false
+    .await;
+    let moved = match res {
+        Ok(moved) => moved,
+        Err(error) => {
+            // The target was not saved, the copies made so far are removed
+            delete_pictures(copies).await;
+            return Err(error);
+        }
+    };
+    delete_ablum(source_id).await?;
+    delete_pictures(moved).await;
+    Ok(())
 }
 
-pub async fn upload_picture(form_data: FormData) -> Option<Picture> {
-    let mut res = None;
-    let uri = UPLOAD_URI.to_string();
-    let response = Request::new(&uri)
-        .method(Method::POST)
-        .body(JsValue::from(form_data))
-        .send()
-        .await
-        .expect("HTTP request failed");
+// The groups are moved to a new album, whose id is returned
+pub async fn split_album(id: String, group_ids: Vec<Uuid>) -> Result<String, ApiError> {
+    let mut album = get_album(Some(id), None).await?;
+    let (mut groups, kept): (Vec<Group>, Vec<Group>) = album
+        .groups
+        .take()
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|group| group_ids.contains(&group.id));
+    album.groups = Some(kept);
+    // Found again by its position, the copy has a new asset id
+    let cover_position = album.cover.as_ref().and_then(|cover| {
+        groups.iter().enumerate().find_map(|(group_index, group)| {
+            let pictures = group.pictures.as_ref()?;
+            let index = pictures.iter().position(|p| &p.asset_id == cover)?;
+            Some((group_index, index))
+        })
+    });
+    if cover_position.is_some() {
+        album.cover = album
+            .groups
+            .iter()
+            .flatten()
+            .filter_map(|group| group.pictures.as_ref())
+            .flatten()
+            .next()
+            .map(|p| p.asset_id.clone());
+    }
 
-    if response.status() == 200 {
-        let res_pic = response.json::<Picture>().await;
-        if let Ok(picture) = res_pic {
-            res = Some(picture);
+    let mut split = Album {
+        title: format!("{} (split)", album.title),
+        caption_style: album.caption_style.clone(),
+        caption_color: album.caption_color.clone(),
+        ..Album::new()
+    };
+    // Saved without groups first to get the id of its folder
+    let stored = update_album(split.clone()).await?;
+    split.id = stored.id;
+    split.version = stored.version;
+    let split_id = split.id.clone();
+
+    let mut copies = Vec::new();
+    let res = async {
+        let moved = rehome(&mut groups, &format!("amaker/{split_id}"), &mut copies).await?;
+        let moved_cover = cover_position.and_then(|(group_index, index)| {
+            let pictures = groups.get(group_index)?.pictures.as_ref()?;
+            pictures.get(index).map(|p| p.asset_id.clone())
+        });
+        split.groups = Some(groups);
+        split.cover = moved_cover.or_else(|| split.cover_picture().map(|p| p.asset_id.clone()));
+        update_album(split).await?;
+        update_album(album).await?;
+        Ok::<_, ApiError>(moved)
+    }
+    .await;
+    match res {
+        Ok(moved) => {
+            delete_pictures(moved).await;
+            Ok(split_id)
+        }
+        Err(error) => {
+            // The source keeps its groups, the copies and the new album are removed
+            delete_pictures(copies).await;
+            _ = delete_ablum(split_id).await;
+            Err(error)
         }
     }
-    res
-}
-
-pub async fn delete_picture(public_id: String) -> bool {
-    load_dotenv!();
-    let mut res = false;
-    let uri = DESTROY_URI.to_string();
-    let apikey = env!("CLD_API_KEY");
-    let secret = env!("CLD_API_SECRET");
-    let ts = js_sys::Date::now().to_string();
-
-    let to_hash = format!("public_id={public_id}&timestamp={ts}{secret}");
-    let digest = digest::digest(&digest::SHA1_FOR_LEGACY_USE_ONLY, to_hash.as_bytes());
-
-    let hash = digest.as_ref();
-    let signature = hash.to_hex();
-
-    if let Ok(form_data) = FormData::new() {
-        let pub_id_res = form_data.append_with_str("public_id", &public_id);
-        let key_res = form_data.append_with_str("api_key", apikey);
-        let secret_res = form_data.append_with_str("api_secret", secret);
-        let ts_res = form_data.append_with_str("timestamp", &ts);
-        let sign_res = form_data.append_with_str("signature", &signature);
-
-        if pub_id_res.is_ok()
-            && key_res.is_ok()
-            && secret_res.is_ok()
-            && ts_res.is_ok()
-            && sign_res.is_ok()
-        {
-            let response = Request::new(&uri)
-                .method(Method::POST)
-                .body(JsValue::from(form_data))
-                .send()
-                .await
-                .expect("HTTP request failed");
-            if response.status() == 200 {
-                res = true;
+}
+
+// Pictures stored outside the folder are copied into it, the public ids of the originals are returned.
+// The public ids of the copies are added as they are made, to remove them on failure
+async fn rehome(
+    groups: &mut [Group],
+    folder: &str,
+    copies: &mut Vec<String>,
+) -> Result<Vec<String>, ApiError> {
+    let mut moved = Vec::new();
+    for pictures in groups
+        .iter_mut()
+        .filter_map(|group| group.pictures.as_mut())
+    {
+        for picture in pictures.iter_mut() {
+            if !picture.public_id.starts_with(&format!("{folder}/")) {
+                let copy = backend::media()
+                    .copy_picture(picture.clone(), folder.to_string())
+                    .await?;
+                copies.push(copy.public_id.clone());
+                moved.push(std::mem::replace(picture, copy).public_id);
             }
         }
     }
-    res
+    Ok(moved)
+}
+
+// The albums are already saved, a picture left behind is found by the storage cleanup
+async fn delete_pictures(public_ids: Vec<String>) {
+    for public_id in public_ids {
+        _ = delete_picture(public_id).await;
+    }
+}
+
+pub async fn delete_ablum(id: String) -> Result<(), ApiError> {
+    backend::albums()
+        .delete_album(id, session::auth_header())
+        .await
+}
+
+pub fn picture_uri(picture: &Picture, size: PictureSize) -> String {
+    backend::media().picture_uri(picture, size)
+}
+
+pub async fn preload_picture(uri: String) -> Result<(), ApiError> {
+    backend::media().preload_picture(uri).await
+}
+
+pub async fn upload_picture(
+    form_data: FormData,
+    on_progress: OnProgress,
+    signal: AbortSignal,
+) -> Result<Picture, ApiError> {
+    backend::media()
+        .upload_picture(form_data, on_progress, signal)
+        .await
+}
+
+pub async fn delete_picture(public_id: String) -> Result<(), ApiError> {
+    backend::media()
+        .delete_picture(public_id, session::auth_header())
+        .await
+}
+
+pub async fn list_pictures() -> Result<Vec<Picture>, ApiError> {
+    backend::media().list_pictures(session::auth_header()).await
 }
@@ -0,0 +1,83 @@
+use std::{future::Future, pin::Pin};
+
+use seed::prelude::*;
+use web_sys::{AbortSignal, FormData};
+
+use crate::{
+    api::{error::ApiError, http::HttpBackend, local::LocalBackend},
+    models::{
+        album::Album,
+        album_filter::AlbumFilter,
+        album_summary::AlbumSummary,
+        config,
+        paged::Paged,
+        picture::Picture,
+        sharing::{AddViewLike, Sharing},
+    },
+};
+
+pub type ApiFuture<T> = Pin<Box<dyn Future<Output = Result<T, ApiError>>>>;
+pub type OnProgress = Box<dyn Fn(u32)>;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum PictureSize {
+    Full,
+    Thumb,
+    // Blurred background of the slideshow
+    VeryLow,
+}
+
+pub trait AlbumBackend {
+    fn login(&self, auth: String) -> ApiFuture<String>;
+    // Public ids of the pictures in the albums of the user
+    fn get_used_public_ids(&self, auth: String) -> ApiFuture<Vec<String>>;
+    fn get_album_summaries(
+        &self,
+        filter: AlbumFilter,
+        cursor: Option<String>,
+        auth: String,
+    ) -> ApiFuture<Paged<AlbumSummary>>;
+    fn get_album(&self, id: String, share_id: String, auth: String) -> ApiFuture<Album>;
+    fn update_album(&self, album: Album, auth: String) -> ApiFuture<Album>;
+    fn delete_album(&self, id: String, auth: String) -> ApiFuture<()>;
+    fn get_my_sharings(&self, cursor: Option<String>, auth: String) -> ApiFuture<Paged<Sharing>>;
+    fn add_sharing(&self, sharing: Sharing, auth: String) -> ApiFuture<String>;
+    fn delete_sharing(&self, id: String, auth: String) -> ApiFuture<()>;
+    fn add_view_like(&self, add_view_like: AddViewLike, auth: String) -> ApiFuture<()>;
+}
+
+pub trait MediaBackend {
+    fn picture_uri(&self, picture: &Picture, size: PictureSize) -> String;
+    fn preload_picture(&self, uri: String) -> ApiFuture<()>;
+    fn upload_picture(
+        &self,
+        form_data: FormData,
+        on_progress: OnProgress,
+        signal: AbortSignal,
+    ) -> ApiFuture<Picture>;
+    fn copy_picture(&self, picture: Picture, folder: String) -> ApiFuture<Picture>;
+    fn delete_picture(&self, public_id: String, auth: String) -> ApiFuture<()>;
+    fn list_pictures(&self, auth: String) -> ApiFuture<Vec<Picture>>;
+}
+
+// The local backend keeps everything in localStorage, enable it with
+// `--features local-backend` or `"local_backend": true` in config.json
+fn use_local() -> bool {
+    cfg!(feature = "local-backend") || config::get().local_backend
+}
+
+pub fn albums() -> Box<dyn AlbumBackend> {
+    if use_local() {
+        Box::new(LocalBackend)
+    } else {
+        Box::new(HttpBackend::new(config::get()))
+    }
+}
+
+pub fn media() -> Box<dyn MediaBackend> {
+    if use_local() {
+        Box::new(LocalBackend)
+    } else {
+        Box::new(HttpBackend::new(config::get()))
+    }
+}
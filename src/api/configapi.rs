@@ -0,0 +1,21 @@
+#![allow(clippy::future_not_send)]
+
+use gloo_net::http::Request;
+
+use crate::{
+    api::error::{check_status, ApiError},
+    models::{config::Config, vars::CONFIG_URI},
+};
+
+pub async fn get_config() -> Result<Config, ApiError> {
+    let response = Request::get(CONFIG_URI)
+        .send()
+        .await
+        .map_err(|_| ApiError::Network)?;
+
+    check_status(&response, 200)?;
+    response
+        .json::<Config>()
+        .await
+        .map_err(|_| ApiError::Deserialization)
+}
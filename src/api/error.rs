@@ -0,0 +1,46 @@
+use gloo_net::http::Response;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ApiError {
+    Network,
+    Serialization,
+    Deserialization,
+    Unauthorized,
+    NotFound,
+    Conflict,
+    Cancelled,
+    Status(u16),
+}
+
+impl ApiError {
+    pub const fn from_status(status: u16) -> Self {
+        match status {
+            401 => Self::Unauthorized,
+            404 => Self::NotFound,
+            409 => Self::Conflict,
+            _ => Self::Status(status),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Network => write!(f, "network unavailable"),
+            Self::Serialization => write!(f, "invalid request"),
+            Self::Deserialization => write!(f, "invalid server response"),
+            Self::Unauthorized => write!(f, "unauthorized"),
+            Self::NotFound => write!(f, "not found"),
+            Self::Conflict => write!(f, "changed by someone else"),
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::Status(status) => write!(f, "server error ({status})"),
+        }
+    }
+}
+
+pub fn check_status(response: &Response, expected: u16) -> Result<(), ApiError> {
+    match response.status() {
+        status if status == expected => Ok(()),
+        status => Err(ApiError::from_status(status)),
+    }
+}
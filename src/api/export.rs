@@ -0,0 +1,398 @@
+#![allow(clippy::future_not_send)]
+
+use gloo_net::http::Request;
+use seed::prelude::*;
+use serde::Serialize;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+use crate::{
+    api::{
+        backend::{self, OnProgress, PictureSize},
+        error::{check_status, ApiError},
+    },
+    models::{album::Album, group::Group, picture::Picture},
+};
+
+// Some browsers start the download after the click returns
+const REVOKE_DELAY: u32 = 10_000;
+
+// Same caption classes as the app, so the export looks the same
+const CAPTION_CSS: &str = include_str!("../../public/caption.css");
+
+const SITE_CSS: &str = "
+body { margin: 0; font-family: sans-serif; background: #f5f5f5; color: #363636; }
+header { display: flex; align-items: center; justify-content: space-between; padding: 1em 2em; background: white; }
+h1 { font-size: 1.5em; color: #485fc7; }
+h2 { font-size: 1.2em; color: #485fc7; }
+button { padding: 0.5em 1em; border: none; border-radius: 4px; background: #485fc7; color: white; cursor: pointer; }
+section { margin: 1em 2em; padding: 1em; background: white; border-radius: 6px; }
+.pictures { display: flex; flex-wrap: wrap; justify-content: center; }
+figure { margin: 0.5em; text-align: center; }
+figure img { width: 200px; height: 200px; object-fit: cover; }
+.caption { display: inline-block; padding: 0.2em 0.8em; text-transform: uppercase; }
+.trip { margin-bottom: 1em; }
+#slideshow { position: fixed; inset: 0; display: flex; flex-direction: column; align-items: center; justify-content: center; background: black; }
+[hidden] { display: none !important; }
+#slide-img { max-width: 100%; max-height: 85%; }
+#slide-text { margin-top: 1em; font-size: 1.5em; white-space: pre-line; }
+";
+
+// Title, then each group with its trip, then its pictures, as in the app
+const SLIDESHOW_JS: &str = "
+let current = 0;
+let timer = null;
+const show = (index) => {
+    current = (index + slides.length) % slides.length;
+    const slide = slides[current];
+    const img = document.getElementById('slide-img');
+    img.hidden = !slide.image;
+    if (slide.image) img.src = slide.image;
+    const text = document.getElementById('slide-text');
+    text.hidden = !slide.text;
+    text.textContent = slide.text || '';
+};
+const close = () => {
+    clearInterval(timer);
+    document.getElementById('slideshow').hidden = true;
+};
+document.getElementById('play').onclick = () => {
+    document.getElementById('slideshow').hidden = false;
+    show(0);
+    timer = setInterval(() => show(current + 1), 4000);
+};
+document.getElementById('slideshow').onclick = close;
+document.onkeydown = (event) => {
+    if (event.key === 'Escape') close();
+    if (event.key === 'ArrowRight') show(current + 1);
+    if (event.key === 'ArrowLeft') show(current - 1);
+};
+";
+
+#[derive(Serialize)]
+struct Slide {
+    image: Option<String>,
+    text: Option<String>,
+}
+
+/// Builds a ZIP with an `index.html` and the pictures of the album, all done in the browser.
+/// The progress is the percentage of pictures downloaded.
+pub async fn export_album(album: &Album, on_progress: OnProgress) -> Result<Vec<u8>, ApiError> {
+    let mut files = Vec::new();
+    let mut images = Vec::new();
+    let total = album.pictures_count();
+    for (index, picture) in pictures(album).enumerate() {
+        let name = format!("images/{:04}.{}", index + 1, picture.format);
+        let uri = backend::media().picture_uri(picture, PictureSize::Full);
+        files.push((name.clone(), download_picture(&uri).await?));
+        images.push(name);
+        on_progress(u32::try_from((index + 1) * 100 / total).unwrap_or(100));
+    }
+    files.insert(
+        0,
+        (
+            "index.html".to_string(),
+            index_html(album, &images).into_bytes(),
+        ),
+    );
+    zip(&files)
+}
+
+async fn download_picture(uri: &str) -> Result<Vec<u8>, ApiError> {
+    let response = Request::get(uri)
+        .send()
+        .await
+        .map_err(|_| ApiError::Network)?;
+
+    check_status(&response, 200)?;
+    response.binary().await.map_err(|_| ApiError::Network)
+}
+
+/// Saves the bytes as a file through a temporary link.
+pub fn save_file(bytes: &[u8], file_name: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let mut options = BlobPropertyBag::new();
+    options.type_("application/zip");
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let link = seed::document()
+        .create_element("a")?
+        .dyn_into::<HtmlAnchorElement>()?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    wasm_bindgen_futures::spawn_local(async move {
+        cmds::timeout(REVOKE_DELAY, || ()).await;
+        _ = Url::revoke_object_url(&url);
+    });
+    Ok(())
+}
+
+fn pictures(album: &Album) -> impl Iterator<Item = &Picture> {
+    groups(album)
+        .iter()
+        .filter_map(|group| group.pictures.as_ref())
+        .flatten()
+}
+
+fn groups(album: &Album) -> &[Group] {
+    album.groups.as_deref().unwrap_or_default()
+}
+
+// ------ ------
+//     Site
+// ------ ------
+// The images are named in the order of the pictures
+fn index_html(album: &Album, images: &[String]) -> String {
+    let caption_class = format!("caption {} {}", album.caption_style, album.caption_color);
+    let mut images = images.iter();
+    let mut sections = String::new();
+    let mut slides = vec![Slide {
+        image: None,
+        text: Some(album.title.clone()),
+    }];
+    for group in groups(album) {
+        sections.push_str(&format!("<section>\n<h2>{}</h2>\n", escape(&group.title)));
+        let trip = group.trip.as_ref().map(|trip| {
+            format!(
+                "{} : {} - {}",
+                trip.transp_mode, trip.origin, trip.destination
+            )
+        });
+        if let Some(trip) = &trip {
+            sections.push_str(&format!(
+                "<p class=\"trip\"><span class=\"{caption_class}\">{}</span></p>\n",
+                escape(trip)
+            ));
+        }
+        slides.push(Slide {
+            image: None,
+            text: Some(trip.map_or_else(
+                || group.title.clone(),
+                |trip| format!("{}\n{trip}", group.title),
+            )),
+        });
+
+        sections.push_str("<div class=\"pictures\">\n");
+        for (picture, image) in group.pictures.iter().flatten().zip(&mut images) {
+            let caption = picture
+                .caption
+                .clone()
+                .filter(|caption| !caption.is_empty());
+            sections.push_str(&format!(
+                "<figure><img src=\"{image}\" alt=\"\" loading=\"lazy\">"
+            ));
+            if let Some(caption) = &caption {
+                sections.push_str(&format!(
+                    "<figcaption><span class=\"{caption_class}\">{}</span></figcaption>",
+                    escape(caption)
+                ));
+            }
+            sections.push_str("</figure>\n");
+            slides.push(Slide {
+                image: Some(image.clone()),
+                text: caption,
+            });
+        }
+        sections.push_str("</div>\n</section>\n");
+    }
+    // A closing tag in a caption would end the script
+    let slides = serde_json::to_string(&slides)
+        .unwrap_or_else(|_| "[]".to_string())
+        .replace("</", "<\\/");
+
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title}</title>
+<style>{CAPTION_CSS}{SITE_CSS}</style>
+</head>
+<body>
+<header><h1>{title}</h1><button id=\"play\">Slideshow</button></header>
+{sections}<div id=\"slideshow\" hidden>
+<img id=\"slide-img\" alt=\"\">
+<p id=\"slide-text\" class=\"{caption_class}\"></p>
+</div>
+<script>
+const slides = {slides};
+{SLIDESHOW_JS}</script>
+</body>
+</html>
+",
+        title = escape(&album.title),
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// ------ ------
+//      Zip
+// ------ ------
+// Stored without compression, the pictures are already compressed
+fn zip(files: &[(String, Vec<u8>)]) -> Result<Vec<u8>, ApiError> {
+    // UTF-8 names
+    const FLAGS: u16 = 0x0800;
+    // 1980-01-01, the earliest date of the format
+    const DATE: u16 = 0x0021;
+
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in files {
+        let offset = size(out.len())?;
+        let crc = crc32(data);
+        let data_size = size(data.len())?;
+        let name_len = u16::try_from(name.len()).map_err(|_| ApiError::Serialization)?;
+
+        put_u32(&mut out, 0x0403_4b50);
+        for value in [20, FLAGS, 0, 0, DATE] {
+            put_u16(&mut out, value);
+        }
+        for value in [crc, data_size, data_size] {
+            put_u32(&mut out, value);
+        }
+        put_u16(&mut out, name_len);
+        put_u16(&mut out, 0);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        put_u32(&mut central, 0x0201_4b50);
+        for value in [20, 20, FLAGS, 0, 0, DATE] {
+            put_u16(&mut central, value);
+        }
+        for value in [crc, data_size, data_size] {
+            put_u32(&mut central, value);
+        }
+        for value in [name_len, 0, 0, 0, 0] {
+            put_u16(&mut central, value);
+        }
+        put_u32(&mut central, 0);
+        put_u32(&mut central, offset);
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let count = u16::try_from(files.len()).map_err(|_| ApiError::Serialization)?;
+    let central_offset = size(out.len())?;
+    let central_size = size(central.len())?;
+    out.append(&mut central);
+    put_u32(&mut out, 0x0605_4b50);
+    for value in [0, 0, count, count] {
+        put_u16(&mut out, value);
+    }
+    put_u32(&mut out, central_size);
+    put_u32(&mut out, central_offset);
+    put_u16(&mut out, 0);
+    Ok(out)
+}
+
+// Larger archives would need ZIP64
+fn size(len: usize) -> Result<u32, ApiError> {
+    u32::try_from(len).map_err(|_| ApiError::Serialization)
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::*;
+
+    use super::{crc32, escape, zip};
+
+    fn u16_at(bytes: &[u8], at: usize) -> usize {
+        usize::from(u16::from_le_bytes([bytes[at], bytes[at + 1]]))
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+    }
+
+    fn offset_at(bytes: &[u8], at: usize) -> usize {
+        usize::try_from(u32_at(bytes, at)).unwrap()
+    }
+
+    // Reads the entries from the central directory, as the unzip tools do
+    fn unzip(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let end = bytes.len() - 22;
+        assert_eq!(u32_at(bytes, end), 0x0605_4b50);
+        let count = u16_at(bytes, end + 10);
+        let mut at = offset_at(bytes, end + 16);
+        let mut files = Vec::new();
+        for _ in 0..count {
+            assert_eq!(u32_at(bytes, at), 0x0201_4b50);
+            let crc = u32_at(bytes, at + 16);
+            let size = offset_at(bytes, at + 20);
+            let name_len = u16_at(bytes, at + 28);
+            let name = String::from_utf8(bytes[at + 46..at + 46 + name_len].to_vec()).unwrap();
+
+            let header = offset_at(bytes, at + 42);
+            assert_eq!(u32_at(bytes, header), 0x0403_4b50);
+            assert_eq!(u32_at(bytes, header + 14), crc);
+            let start = header + 30 + u16_at(bytes, header + 26) + u16_at(bytes, header + 28);
+            let data = bytes[start..start + size].to_vec();
+            assert_eq!(crc32(&data), crc);
+
+            files.push((name, data));
+            at += 46 + name_len;
+        }
+        files
+    }
+
+    #[wasm_bindgen_test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn zip_round_trips() {
+        let files = vec![
+            ("index.html".to_string(), b"<html></html>".to_vec()),
+            ("images/0001.jpg".to_string(), vec![0xFF, 0xD8, 0x00, 0xFF]),
+            ("images/été.txt".to_string(), Vec::new()),
+        ];
+        assert_eq!(unzip(&zip(&files).unwrap()), files);
+    }
+
+    #[wasm_bindgen_test]
+    fn zip_of_nothing_is_an_empty_archive() {
+        let bytes = zip(&[]).unwrap();
+        assert_eq!(bytes.len(), 22);
+        assert!(unzip(&bytes).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn escape_replaces_the_special_characters() {
+        assert_eq!(
+            escape("<b>Tom & \"Jerry's\"</b>"),
+            "&lt;b&gt;Tom &amp; &quot;Jerry&#39;s&quot;&lt;/b&gt;"
+        );
+    }
+}
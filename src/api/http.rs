@@ -0,0 +1,397 @@
+use gloo_net::http::{Method, Request};
+use seed::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortSignal, FormData, ProgressEvent, XmlHttpRequest};
+
+use crate::{
+    api::{
+        backend::{AlbumBackend, ApiFuture, MediaBackend, OnProgress, PictureSize},
+        error::{check_status, ApiError},
+    },
+    models::{
+        album::Album,
+        album_filter::AlbumFilter,
+        album_summary::AlbumSummary,
+        config::Config,
+        paged::{Paged, PAGE_SIZE},
+        picture::Picture,
+        sharing::{AddViewLike, Sharing},
+        vars::AUTH_HEAD,
+    },
+};
+
+pub struct HttpBackend {
+    config: Config,
+}
+
+impl HttpBackend {
+    pub const fn new(config: Config) -> Self {
+        Self { config }
+    }
+}
+
+// Limit, cursor and filters of a list endpoint
+fn page_query(cursor: Option<String>, params: Vec<(&str, String)>) -> String {
+    let mut query = vec![format!("limit={PAGE_SIZE}")];
+    for (key, value) in cursor
+        .map(|cursor| ("cursor", cursor))
+        .into_iter()
+        .chain(params)
+    {
+        query.push(format!(
+            "{key}={}",
+            String::from(js_sys::encode_uri_component(&value))
+        ));
+    }
+    query.join("&")
+}
+
+impl AlbumBackend for HttpBackend {
+    fn login(&self, auth: String) -> ApiFuture<String> {
+        let uri = self.config.base_uri.clone() + "login";
+        Box::pin(async move {
+            let response = Request::new(&uri)
+                .method(Method::POST)
+                .header(AUTH_HEAD, &auth)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            response
+                .json::<String>()
+                .await
+                .map_err(|_| ApiError::Deserialization)
+        })
+    }
+
+    fn get_used_public_ids(&self, auth: String) -> ApiFuture<Vec<String>> {
+        let uri = self.config.base_uri.clone() + "usedpictures";
+        Box::pin(async move {
+            let response = Request::get(&uri)
+                .header(AUTH_HEAD, &auth)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            response
+                .json::<Vec<String>>()
+                .await
+                .map_err(|_| ApiError::Deserialization)
+        })
+    }
+
+    fn get_album_summaries(
+        &self,
+        filter: AlbumFilter,
+        cursor: Option<String>,
+        auth: String,
+    ) -> ApiFuture<Paged<AlbumSummary>> {
+        let uri = format!(
+            "{}myalbumsummaries?{}",
+            self.config.base_uri,
+            page_query(cursor, filter.params())
+        );
+        Box::pin(async move {
+            let response = Request::get(&uri)
+                .header(AUTH_HEAD, &auth)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            response
+                .json::<Paged<AlbumSummary>>()
+                .await
+                .map_err(|_| ApiError::Deserialization)
+        })
+    }
+
+    fn get_album(&self, id: String, share_id: String, auth: String) -> ApiFuture<Album> {
+        let uri = format!(
+            "{}getalbum?id={id}&share_id={share_id}",
+            self.config.base_uri
+        );
+        Box::pin(async move {
+            let response = Request::get(&uri)
+                .header(AUTH_HEAD, &auth)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            response
+                .json::<Album>()
+                .await
+                .map_err(|_| ApiError::Deserialization)
+        })
+    }
+
+    fn update_album(&self, album: Album, auth: String) -> ApiFuture<Album> {
+        let uri = self.config.base_uri.clone() + "editalbum";
+        Box::pin(async move {
+            let response = Request::new(&uri)
+                .method(Method::PUT)
+                .header(AUTH_HEAD, &auth)
+                .json(&album)
+                .map_err(|_| ApiError::Serialization)?
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            response
+                .json::<Album>()
+                .await
+                .map_err(|_| ApiError::Deserialization)
+        })
+    }
+
+    fn delete_album(&self, id: String, auth: String) -> ApiFuture<()> {
+        let delete_uri = format!("{}deletealbum?id={id}", self.config.base_uri);
+        Box::pin(async move {
+            let delete_response = Request::new(&delete_uri)
+                .header(AUTH_HEAD, &auth)
+                .method(Method::DELETE)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&delete_response, 204)
+        })
+    }
+
+    fn get_my_sharings(&self, cursor: Option<String>, auth: String) -> ApiFuture<Paged<Sharing>> {
+        let uri = format!(
+            "{}mysharings?{}",
+            self.config.base_uri,
+            page_query(cursor, Vec::new())
+        );
+        Box::pin(async move {
+            let response = Request::new(&uri)
+                .header(AUTH_HEAD, &auth)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            response
+                .json::<Paged<Sharing>>()
+                .await
+                .map_err(|_| ApiError::Deserialization)
+        })
+    }
+
+    fn add_sharing(&self, sharing: Sharing, auth: String) -> ApiFuture<String> {
+        let uri = self.config.base_uri.clone() + "addsharing";
+        Box::pin(async move {
+            let response = Request::new(&uri)
+                .method(Method::POST)
+                .header(AUTH_HEAD, &auth)
+                .json(&sharing)
+                .map_err(|_| ApiError::Serialization)?
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            response
+                .json::<String>()
+                .await
+                .map_err(|_| ApiError::Deserialization)
+        })
+    }
+
+    fn delete_sharing(&self, id: String, auth: String) -> ApiFuture<()> {
+        let delete_uri = format!("{}deletesharing?id={id}", self.config.base_uri);
+        Box::pin(async move {
+            let delete_response = Request::new(&delete_uri)
+                .header(AUTH_HEAD, &auth)
+                .method(Method::DELETE)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&delete_response, 204)
+        })
+    }
+
+    fn add_view_like(&self, add_view_like: AddViewLike, auth: String) -> ApiFuture<()> {
+        let uri = self.config.base_uri.clone() + "addviewlike";
+        Box::pin(async move {
+            let response = Request::new(&uri)
+                .method(Method::POST)
+                .header(AUTH_HEAD, &auth)
+                .json(&add_view_like)
+                .map_err(|_| ApiError::Serialization)?
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)
+        })
+    }
+}
+
+impl MediaBackend for HttpBackend {
+    fn picture_uri(&self, picture: &Picture, size: PictureSize) -> String {
+        let uri = match size {
+            PictureSize::Full => self.config.img_uri(),
+            PictureSize::Thumb => self.config.thumb_uri(),
+            PictureSize::VeryLow => self.config.very_low_uri(),
+        };
+        format!("{uri}{}.{}", picture.public_id, picture.format)
+    }
+
+    fn preload_picture(&self, uri: String) -> ApiFuture<()> {
+        Box::pin(async move {
+            let response = Request::new(&uri)
+                .method(Method::GET)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)
+        })
+    }
+
+    fn upload_picture(
+        &self,
+        form_data: FormData,
+        on_progress: OnProgress,
+        signal: AbortSignal,
+    ) -> ApiFuture<Picture> {
+        let uri = self.config.upload_uri();
+        Box::pin(async move {
+            // XMLHttpRequest instead of fetch to follow the upload progress
+            let xhr = XmlHttpRequest::new().map_err(|_| ApiError::Network)?;
+            xhr.open("POST", &uri).map_err(|_| ApiError::Network)?;
+
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let on_upload_progress =
+                Closure::<dyn Fn(ProgressEvent)>::new(move |event: ProgressEvent| {
+                    if event.length_computable() && event.total() > 0. {
+                        on_progress((event.loaded() / event.total() * 100.) as u32);
+                    }
+                });
+            let upload = xhr.upload().map_err(|_| ApiError::Network)?;
+            upload.set_onprogress(Some(on_upload_progress.as_ref().unchecked_ref()));
+
+            let xhr_abort = xhr.clone();
+            let on_abort = Closure::<dyn Fn()>::new(move || {
+                _ = xhr_abort.abort();
+            });
+            signal.set_onabort(Some(on_abort.as_ref().unchecked_ref()));
+
+            let done = js_sys::Promise::new(&mut |resolve, reject| {
+                xhr.set_onload(Some(&resolve));
+                xhr.set_onerror(Some(&reject));
+                xhr.set_onabort(Some(&reject));
+            });
+            // Cancelled before the handler was set
+            if signal.aborted() {
+                signal.set_onabort(None);
+                return Err(ApiError::Cancelled);
+            }
+            xhr.send_with_opt_form_data(Some(&form_data))
+                .map_err(|_| ApiError::Network)?;
+            let res = JsFuture::from(done).await;
+            signal.set_onabort(None);
+
+            if signal.aborted() {
+                return Err(ApiError::Cancelled);
+            }
+            res.map_err(|_| ApiError::Network)?;
+
+            let status = xhr.status().map_err(|_| ApiError::Network)?;
+            if status != 200 {
+                return Err(ApiError::from_status(status));
+            }
+            let text = xhr
+                .response_text()
+                .map_err(|_| ApiError::Deserialization)?
+                .unwrap_or_default();
+            serde_json::from_str::<Picture>(&text).map_err(|_| ApiError::Deserialization)
+        })
+    }
+
+    // Uploaded again from its url, the caption and metadata are kept
+    fn copy_picture(&self, picture: Picture, folder: String) -> ApiFuture<Picture> {
+        let uri = self.config.upload_uri();
+        let file = format!(
+            "{}{}.{}",
+            self.config.img_uri(),
+            picture.public_id,
+            picture.format
+        );
+        let upload_preset = self.config.upload_preset.clone();
+        Box::pin(async move {
+            let form_data = FormData::new().map_err(|_| ApiError::Serialization)?;
+            for (key, value) in [
+                ("file", file),
+                ("upload_preset", upload_preset),
+                ("folder", folder),
+            ] {
+                form_data
+                    .append_with_str(key, &value)
+                    .map_err(|_| ApiError::Serialization)?;
+            }
+            let response = Request::new(&uri)
+                .method(Method::POST)
+                .body(form_data)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            let copy = response
+                .json::<Picture>()
+                .await
+                .map_err(|_| ApiError::Deserialization)?;
+            Ok(Picture {
+                asset_id: copy.asset_id,
+                public_id: copy.public_id,
+                format: copy.format,
+                created_at: copy.created_at,
+                ..picture
+            })
+        })
+    }
+
+    fn delete_picture(&self, public_id: String, auth: String) -> ApiFuture<()> {
+        let delete_uri = format!(
+            "{}deletepicture?public_id={}",
+            self.config.base_uri,
+            String::from(js_sys::encode_uri_component(&public_id))
+        );
+        Box::pin(async move {
+            let delete_response = Request::new(&delete_uri)
+                .header(AUTH_HEAD, &auth)
+                .method(Method::DELETE)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&delete_response, 204)
+        })
+    }
+
+    fn list_pictures(&self, auth: String) -> ApiFuture<Vec<Picture>> {
+        let uri = self.config.base_uri.clone() + "listpictures";
+        Box::pin(async move {
+            let response = Request::new(&uri)
+                .header(AUTH_HEAD, &auth)
+                .method(Method::GET)
+                .send()
+                .await
+                .map_err(|_| ApiError::Network)?;
+
+            check_status(&response, 200)?;
+            response
+                .json::<Vec<Picture>>()
+                .await
+                .map_err(|_| ApiError::Deserialization)
+        })
+    }
+}
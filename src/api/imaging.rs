@@ -0,0 +1,115 @@
+use std::path::Path;
+
+use seed::prelude::*;
+use web_sys::{
+    Blob, CanvasRenderingContext2d, File, FilePropertyBag, HtmlCanvasElement, ImageBitmap,
+};
+
+use crate::models::config::{self, Resize};
+
+// Animated or vector pictures are uploaded as is
+const RESIZABLE_TYPES: [&str; 4] = ["image/jpeg", "image/png", "image/webp", "image/bmp"];
+
+/// Downsizes the picture according to the `resize` config.
+/// The original file is returned when it is already small enough or when the browser fails.
+pub async fn prepare(file: &File) -> File {
+    let Some(resize) = config::get().resize else {
+        return file.clone();
+    };
+    if !RESIZABLE_TYPES.contains(&file.type_().as_str()) {
+        return file.clone();
+    }
+    resize_file(file, &resize)
+        .await
+        .unwrap_or_else(|| file.clone())
+}
+
+async fn resize_file(file: &File, resize: &Resize) -> Option<File> {
+    let bitmap = decode(file).await?;
+    let (width, height) = (bitmap.width(), bitmap.height());
+    let largest = width.max(height);
+    if largest <= resize.max_dimension {
+        bitmap.close();
+        return None;
+    }
+
+    let scale = f64::from(resize.max_dimension) / f64::from(largest);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let (target_width, target_height) = (
+        (f64::from(width) * scale).round() as u32,
+        (f64::from(height) * scale).round() as u32,
+    );
+
+    let canvas = web_sys::window()?
+        .document()?
+        .create_element("canvas")
+        .ok()?
+        .dyn_into::<HtmlCanvasElement>()
+        .ok()?;
+    canvas.set_width(target_width);
+    canvas.set_height(target_height);
+    let context = canvas
+        .get_context("2d")
+        .ok()??
+        .dyn_into::<CanvasRenderingContext2d>()
+        .ok()?;
+    let drawn = context.draw_image_with_image_bitmap_and_dw_and_dh(
+        &bitmap,
+        0.,
+        0.,
+        f64::from(target_width),
+        f64::from(target_height),
+    );
+    bitmap.close();
+    drawn.ok()?;
+
+    let blob = encode(&canvas, resize).await?;
+    let stem = Path::new(&file.name())
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let name = format!("{stem}.{}", resize.format.extension());
+    let mut options = FilePropertyBag::new();
+    options.type_(resize.format.mime());
+    File::new_with_blob_sequence_and_options(&js_sys::Array::of1(&blob), &name, &options).ok()
+}
+
+// Decodes the picture with the EXIF orientation applied
+async fn decode(file: &File) -> Option<ImageBitmap> {
+    let window = web_sys::window()?;
+    // The imageOrientation option is not exposed by web-sys
+    let options = js_sys::Object::new();
+    js_sys::Reflect::set(&options, &"imageOrientation".into(), &"from-image".into()).ok()?;
+    let create_image_bitmap = js_sys::Reflect::get(&window, &"createImageBitmap".into())
+        .ok()?
+        .dyn_into::<js_sys::Function>()
+        .ok()?;
+    let promise = create_image_bitmap
+        .call2(&window, file, &options)
+        .ok()?
+        .dyn_into::<js_sys::Promise>()
+        .ok()?;
+    JsFuture::from(promise)
+        .await
+        .ok()?
+        .dyn_into::<ImageBitmap>()
+        .ok()
+}
+
+async fn encode(canvas: &HtmlCanvasElement, resize: &Resize) -> Option<Blob> {
+    let mime = resize.format.mime();
+    let encoded = js_sys::Promise::new(&mut |resolve, reject| {
+        let quality = JsValue::from_f64(resize.quality);
+        if let Err(error) = canvas.to_blob_with_type_and_encoder_options(&resolve, mime, &quality) {
+            _ = reject.call1(&JsValue::NULL, &error);
+        }
+    });
+    let blob = JsFuture::from(encoded)
+        .await
+        .ok()?
+        .dyn_into::<Blob>()
+        .ok()?;
+    // Browsers without an encoder for the format fall back to PNG
+    (blob.type_() == mime).then_some(blob)
+}
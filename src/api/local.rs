@@ -0,0 +1,449 @@
+use std::{cell::RefCell, collections::HashMap, path::Path};
+
+use seed::{
+    browser::web_storage::{LocalStorage, WebStorage},
+    prelude::*,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use uuid::Uuid;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortSignal, File, FormData};
+
+use crate::{
+    api::{
+        backend::{AlbumBackend, ApiFuture, MediaBackend, OnProgress, PictureSize},
+        error::ApiError,
+    },
+    models::{
+        album::Album,
+        album_filter::AlbumFilter,
+        album_summary::AlbumSummary,
+        paged::Paged,
+        picture::Picture,
+        sharing::{AddViewLike, Sharing},
+    },
+};
+
+const ALBUMS_KEY: &str = "amaker_albums";
+const SHARINGS_KEY: &str = "amaker_sharings";
+const PICTURES_KEY: &str = "amaker_pictures";
+// Followed by the public id, the picture is kept as a data url
+const PICTURE_DATA_KEY: &str = "amaker_picture_data_";
+
+// Data urls already read from localStorage, by public id
+thread_local! {
+    static DATA_URLS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+pub struct LocalBackend;
+
+fn load<T: DeserializeOwned + Default>(key: &str) -> T {
+    LocalStorage::get(key).unwrap_or_default()
+}
+
+fn save<T: Serialize>(key: &str, value: &T) -> Result<(), ApiError> {
+    LocalStorage::insert(key, value).map_err(|_| ApiError::Serialization)
+}
+
+fn now() -> Option<String> {
+    js_sys::Date::new_0().to_iso_string().as_string()
+}
+
+impl AlbumBackend for LocalBackend {
+    fn login(&self, _auth: String) -> ApiFuture<String> {
+        Box::pin(async { Ok(Uuid::new_v4().to_string()) })
+    }
+
+    fn get_used_public_ids(&self, _auth: String) -> ApiFuture<Vec<String>> {
+        Box::pin(async {
+            let albums: Vec<Album> = load(ALBUMS_KEY);
+            Ok(albums.iter().flat_map(Album::public_ids).collect())
+        })
+    }
+
+    fn get_album_summaries(
+        &self,
+        filter: AlbumFilter,
+        cursor: Option<String>,
+        _auth: String,
+    ) -> ApiFuture<Paged<AlbumSummary>> {
+        Box::pin(async move {
+            let albums: Vec<Album> = load(ALBUMS_KEY);
+            let sharings: Vec<Sharing> = load(SHARINGS_KEY);
+            let summaries = filter
+                .apply(&albums, &sharings)
+                .into_iter()
+                .map(|album| {
+                    let shares_count = sharings.iter().filter(|s| s.album_id == album.id).count();
+                    AlbumSummary::new(album, shares_count)
+                })
+                .collect();
+            Ok(Paged::from_offset(summaries, cursor.as_deref()))
+        })
+    }
+
+    fn get_album(&self, id: String, share_id: String, _auth: String) -> ApiFuture<Album> {
+        Box::pin(async move {
+            let mut album_id = id;
+            if !share_id.is_empty() {
+                let sharings: Vec<Sharing> = load(SHARINGS_KEY);
+                let sharing = sharings
+                    .into_iter()
+                    .find(|s| s.id == share_id)
+                    .ok_or(ApiError::NotFound)?;
+                album_id = sharing.album_id;
+            }
+            let albums: Vec<Album> = load(ALBUMS_KEY);
+            albums
+                .into_iter()
+                .find(|a| a.id == album_id)
+                .ok_or(ApiError::NotFound)
+        })
+    }
+
+    fn update_album(&self, mut album: Album, _auth: String) -> ApiFuture<Album> {
+        Box::pin(async move {
+            let mut albums: Vec<Album> = load(ALBUMS_KEY);
+            if album.id.is_empty() {
+                album.id = Uuid::new_v4().to_string();
+            }
+            let id = album.id.clone();
+            let existing = albums.iter_mut().find(|a| a.id == id);
+            if existing
+                .as_ref()
+                .map_or(false, |existing| existing.version != album.version)
+            {
+                return Err(ApiError::Conflict);
+            }
+            album.version += 1;
+            album.updated_at = now();
+            match existing {
+                Some(existing) => *existing = album.clone(),
+                None => albums.push(album.clone()),
+            }
+            save(ALBUMS_KEY, &albums)?;
+            Ok(album)
+        })
+    }
+
+    fn delete_album(&self, id: String, _auth: String) -> ApiFuture<()> {
+        Box::pin(async move {
+            let mut albums: Vec<Album> = load(ALBUMS_KEY);
+            let index = albums
+                .iter()
+                .position(|a| a.id == id)
+                .ok_or(ApiError::NotFound)?;
+            albums.remove(index);
+            save(ALBUMS_KEY, &albums)
+        })
+    }
+
+    fn get_my_sharings(&self, cursor: Option<String>, _auth: String) -> ApiFuture<Paged<Sharing>> {
+        Box::pin(async move { Ok(Paged::from_offset(load(SHARINGS_KEY), cursor.as_deref())) })
+    }
+
+    fn add_sharing(&self, mut sharing: Sharing, _auth: String) -> ApiFuture<String> {
+        Box::pin(async move {
+            let albums: Vec<Album> = load(ALBUMS_KEY);
+            let album = albums
+                .iter()
+                .find(|a| a.id == sharing.album_id)
+                .ok_or(ApiError::NotFound)?;
+            sharing.id = Uuid::new_v4().to_string();
+            sharing.album_name = album.title.clone();
+
+            let mut sharings: Vec<Sharing> = load(SHARINGS_KEY);
+            let id = sharing.id.clone();
+            sharings.push(sharing);
+            save(SHARINGS_KEY, &sharings)?;
+            Ok(id)
+        })
+    }
+
+    fn delete_sharing(&self, id: String, _auth: String) -> ApiFuture<()> {
+        Box::pin(async move {
+            let mut sharings: Vec<Sharing> = load(SHARINGS_KEY);
+            let index = sharings
+                .iter()
+                .position(|s| s.id == id)
+                .ok_or(ApiError::NotFound)?;
+            sharings.remove(index);
+            save(SHARINGS_KEY, &sharings)
+        })
+    }
+
+    fn add_view_like(&self, add_view_like: AddViewLike, _auth: String) -> ApiFuture<()> {
+        Box::pin(async move {
+            let mut sharings: Vec<Sharing> = load(SHARINGS_KEY);
+            let sharing = sharings
+                .iter_mut()
+                .find(|s| s.id == add_view_like.share_id)
+                .ok_or(ApiError::NotFound)?;
+            if add_view_like.view {
+                sharing.nb_view += 1;
+            }
+            if add_view_like.like {
+                sharing.nb_like += 1;
+            }
+            save(SHARINGS_KEY, &sharings)
+        })
+    }
+}
+
+fn data_url(public_id: &str) -> Option<String> {
+    DATA_URLS.with(|urls| {
+        let mut urls = urls.borrow_mut();
+        if !urls.contains_key(public_id) {
+            let url: String = LocalStorage::get(format!("{PICTURE_DATA_KEY}{public_id}")).ok()?;
+            urls.insert(public_id.to_string(), url);
+        }
+        urls.get(public_id).cloned()
+    })
+}
+
+fn save_data_url(public_id: &str, url: String) -> Result<(), ApiError> {
+    save(&format!("{PICTURE_DATA_KEY}{public_id}"), &url)?;
+    DATA_URLS.with(|urls| urls.borrow_mut().insert(public_id.to_string(), url));
+    Ok(())
+}
+
+async fn read_data_url(file: &File, format: &str) -> Result<String, ApiError> {
+    let buffer = JsFuture::from(file.array_buffer())
+        .await
+        .map_err(|_| ApiError::Serialization)?;
+    let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+    // The type is empty when the browser does not know the extension
+    let mime = Some(file.type_())
+        .filter(|mime| !mime.is_empty())
+        .unwrap_or_else(|| match format {
+            "jpg" => "image/jpeg".to_string(),
+            _ => format!("image/{format}"),
+        });
+    Ok(format!("data:{mime};base64,{}", base64::encode(bytes)))
+}
+
+// Pictures are kept in localStorage as data urls, without any resizing
+impl MediaBackend for LocalBackend {
+    fn picture_uri(&self, picture: &Picture, _size: PictureSize) -> String {
+        data_url(&picture.public_id).unwrap_or_default()
+    }
+
+    fn preload_picture(&self, _uri: String) -> ApiFuture<()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn upload_picture(
+        &self,
+        form_data: FormData,
+        on_progress: OnProgress,
+        _signal: AbortSignal,
+    ) -> ApiFuture<Picture> {
+        Box::pin(async move {
+            on_progress(100);
+            let file = form_data
+                .get("file")
+                .dyn_into::<File>()
+                .map_err(|_| ApiError::Serialization)?;
+            let folder = form_data.get("folder").as_string().unwrap_or_default();
+            let name = file.name();
+            let format = Path::new(&name)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("jpg")
+                .to_lowercase();
+            let original_filename = Path::new(&name)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(ToString::to_string);
+            let asset_id = Uuid::new_v4().to_string();
+            let public_id = format!("{folder}/{asset_id}");
+            save_data_url(&public_id, read_data_url(&file, &format).await?)?;
+            let picture = Picture {
+                public_id,
+                asset_id,
+                format,
+                original_filename,
+                created_at: now(),
+                ..Picture::default()
+            };
+            let mut pictures: Vec<Picture> = load(PICTURES_KEY);
+            pictures.push(picture.clone());
+            save(PICTURES_KEY, &pictures)?;
+            Ok(picture)
+        })
+    }
+
+    fn copy_picture(&self, picture: Picture, folder: String) -> ApiFuture<Picture> {
+        Box::pin(async move {
+            let asset_id = Uuid::new_v4().to_string();
+            let copy = Picture {
+                public_id: format!("{folder}/{asset_id}"),
+                asset_id,
+                created_at: now(),
+                ..picture
+            };
+            if let Some(url) = data_url(&picture.public_id) {
+                save_data_url(&copy.public_id, url)?;
+            }
+            let mut pictures: Vec<Picture> = load(PICTURES_KEY);
+            pictures.push(copy.clone());
+            save(PICTURES_KEY, &pictures)?;
+            Ok(copy)
+        })
+    }
+
+    fn delete_picture(&self, public_id: String, _auth: String) -> ApiFuture<()> {
+        Box::pin(async move {
+            let mut pictures: Vec<Picture> = load(PICTURES_KEY);
+            pictures.retain(|p| p.public_id != public_id);
+            LocalStorage::remove(format!("{PICTURE_DATA_KEY}{public_id}"))
+                .map_err(|_| ApiError::Serialization)?;
+            DATA_URLS.with(|urls| urls.borrow_mut().remove(&public_id));
+            save(PICTURES_KEY, &pictures)
+        })
+    }
+
+    fn list_pictures(&self, _auth: String) -> ApiFuture<Vec<Picture>> {
+        Box::pin(async { Ok(load(PICTURES_KEY)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use seed::{
+        browser::web_storage::{LocalStorage, WebStorage},
+        prelude::*,
+    };
+    use wasm_bindgen_test::*;
+    use web_sys::{AbortController, File, FormData};
+
+    use super::LocalBackend;
+    use crate::{
+        api::{
+            backend::{AlbumBackend, MediaBackend, PictureSize},
+            error::ApiError,
+        },
+        models::{album::Album, sharing::AddViewLike, sharing::Sharing},
+    };
+
+    fn new_album(title: &str) -> Album {
+        let mut album = Album::new();
+        album.title = title.to_string();
+        album
+    }
+
+    fn upload_form(folder: &str) -> FormData {
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&[1_u8, 2, 3][..]));
+        let file = File::new_with_u8_array_sequence(&parts, "photo.JPG").unwrap();
+        let form_data = FormData::new().unwrap();
+        form_data.append_with_blob("file", &file).unwrap();
+        form_data.append_with_str("folder", folder).unwrap();
+        form_data
+    }
+
+    #[wasm_bindgen_test]
+    async fn saves_and_gets_album() {
+        LocalStorage::clear().unwrap();
+        let saved = LocalBackend
+            .update_album(new_album("Rome"), String::new())
+            .await
+            .unwrap();
+        assert_eq!(saved.version, 1);
+        let album = LocalBackend
+            .get_album(saved.id.clone(), String::new(), String::new())
+            .await
+            .unwrap();
+        assert_eq!(album.id, saved.id);
+        assert_eq!(album.title, "Rome");
+        assert_eq!(album.version, 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn rejects_outdated_version() {
+        LocalStorage::clear().unwrap();
+        let mut album = new_album("Rome");
+        album.id = LocalBackend
+            .update_album(album.clone(), String::new())
+            .await
+            .unwrap()
+            .id;
+        let res = LocalBackend.update_album(album, String::new()).await;
+        assert_eq!(res.err(), Some(ApiError::Conflict));
+    }
+
+    #[wasm_bindgen_test]
+    async fn counts_views_and_likes_of_sharing() {
+        LocalStorage::clear().unwrap();
+        let album_id = LocalBackend
+            .update_album(new_album("Rome"), String::new())
+            .await
+            .unwrap()
+            .id;
+        let sharing = Sharing {
+            id: String::new(),
+            album_id: album_id.clone(),
+            album_name: String::new(),
+            nb_like: 0,
+            nb_view: 0,
+        };
+        let share_id = LocalBackend
+            .add_sharing(sharing, String::new())
+            .await
+            .unwrap();
+        let add_view_like = AddViewLike {
+            view: true,
+            like: true,
+            share_id: share_id.clone(),
+        };
+        LocalBackend
+            .add_view_like(add_view_like, String::new())
+            .await
+            .unwrap();
+
+        let page = LocalBackend
+            .get_my_sharings(None, String::new())
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].album_name, "Rome");
+        assert_eq!((page.items[0].nb_view, page.items[0].nb_like), (1, 1));
+        let shared = LocalBackend
+            .get_album(String::new(), share_id, String::new())
+            .await
+            .unwrap();
+        assert_eq!(shared.id, album_id);
+    }
+
+    #[wasm_bindgen_test]
+    async fn stores_copies_and_deletes_picture_data() {
+        LocalStorage::clear().unwrap();
+        let signal = AbortController::new().unwrap().signal();
+        let picture = LocalBackend
+            .upload_picture(upload_form("amaker/1"), Box::new(|_| ()), signal)
+            .await
+            .unwrap();
+        assert!(picture.public_id.starts_with("amaker/1/"));
+        assert_eq!(picture.format, "jpg");
+        assert_eq!(picture.original_filename.as_deref(), Some("photo"));
+        let url = LocalBackend.picture_uri(&picture, PictureSize::Thumb);
+        assert!(url.starts_with("data:image/jpeg;base64,"));
+
+        let copy = LocalBackend
+            .copy_picture(picture.clone(), "amaker/2".to_string())
+            .await
+            .unwrap();
+        assert!(copy.public_id.starts_with("amaker/2/"));
+        assert_eq!(LocalBackend.picture_uri(&copy, PictureSize::Full), url);
+
+        LocalBackend
+            .delete_picture(picture.public_id.clone(), String::new())
+            .await
+            .unwrap();
+        assert!(LocalBackend
+            .picture_uri(&picture, PictureSize::Full)
+            .is_empty());
+        let pictures = LocalBackend.list_pictures(String::new()).await.unwrap();
+        assert_eq!(pictures.len(), 1);
+        assert_eq!(pictures[0].public_id, copy.public_id);
+    }
+}
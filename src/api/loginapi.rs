@@ -0,0 +1,11 @@
+#![allow(clippy::future_not_send)]
+
+use crate::{
+    api::{backend, error::ApiError},
+    models::session::Session,
+};
+
+pub async fn login(auth: String) -> Result<Session, ApiError> {
+    let token = backend::albums().login(auth).await?;
+    Ok(Session::new(token))
+}
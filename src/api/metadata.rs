@@ -0,0 +1,216 @@
+use std::io::Cursor;
+
+use exif::{DateTime, Exif, In, Reader, Tag, Value};
+use seed::prelude::*;
+use web_sys::File;
+
+use crate::models::picture::{Gps, Picture};
+
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    pub taken_at: Option<String>,
+    pub gps: Option<Gps>,
+    pub camera: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl Metadata {
+    // The dimensions returned by the upload are kept since the file may have been resized
+    pub fn apply(self, picture: &mut Picture) {
+        picture.taken_at = self.taken_at;
+        picture.gps = self.gps;
+        picture.camera = self.camera;
+        picture.width = picture.width.or(self.width);
+        picture.height = picture.height.or(self.height);
+    }
+}
+
+// The EXIF is at the start of the file
+const HEADER_SIZE: i32 = 128 * 1024;
+
+/// Reads the EXIF of the original file.
+/// Returns empty metadata when the file has none.
+pub async fn read(file: &File) -> Metadata {
+    let Ok(header) = file.slice_with_i32_and_i32(0, HEADER_SIZE) else {
+        return Metadata::default();
+    };
+    let Ok(buffer) = JsFuture::from(header.array_buffer()).await else {
+        return Metadata::default();
+    };
+    let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+    Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .map(|exif| parse(&exif))
+        .unwrap_or_default()
+}
+
+fn parse(exif: &Exif) -> Metadata {
+    Metadata {
+        taken_at: taken_at(exif),
+        gps: gps(exif),
+        camera: camera(exif),
+        width: uint(exif, Tag::PixelXDimension),
+        height: uint(exif, Tag::PixelYDimension),
+    }
+}
+
+fn ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => {
+            let value = String::from_utf8_lossy(values.first()?);
+            let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+            (!value.is_empty()).then(|| value.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn uint(exif: &Exif, tag: Tag) -> Option<u32> {
+    exif.get_field(tag, In::PRIMARY)?.value.get_uint(0)
+}
+
+fn taken_at(exif: &Exif) -> Option<String> {
+    let field = exif
+        .get_field(Tag::DateTimeOriginal, In::PRIMARY)
+        .or_else(|| exif.get_field(Tag::DateTime, In::PRIMARY))?;
+    let Value::Ascii(values) = &field.value else {
+        return None;
+    };
+    let date = DateTime::from_ascii(values.first()?).ok()?;
+    Some(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        date.year, date.month, date.day, date.hour, date.minute, date.second
+    ))
+}
+
+fn camera(exif: &Exif) -> Option<String> {
+    let make = ascii(exif, Tag::Make);
+    let model = ascii(exif, Tag::Model);
+    match (make, model) {
+        // The model often already starts with the make
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{make} {model}")),
+        (make, model) => make.or(model),
+    }
+}
+
+fn gps(exif: &Exif) -> Option<Gps> {
+    let lat = coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, "S")?;
+    let lon = coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")?;
+    Some(Gps { lat, lon })
+}
+
+// Degrees, minutes and seconds to decimal degrees
+fn coordinate(exif: &Exif, tag: Tag, ref_tag: Tag, negative_ref: &str) -> Option<f64> {
+    let Value::Rational(values) = &exif.get_field(tag, In::PRIMARY)?.value else {
+        return None;
+    };
+    let degrees = values.first()?.to_f64();
+    let minutes = values.get(1).map_or(0., exif::Rational::to_f64);
+    let seconds = values.get(2).map_or(0., exif::Rational::to_f64);
+    let decimal = degrees + minutes / 60. + seconds / 3600.;
+    if ascii(exif, ref_tag).as_deref() == Some(negative_ref) {
+        Some(-decimal)
+    } else {
+        Some(decimal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use exif::{experimental::Writer, Exif, Field, In, Rational, Reader, Tag, Value};
+    use wasm_bindgen_test::*;
+
+    use super::parse;
+
+    fn exif(fields: &[Field]) -> Exif {
+        let mut writer = Writer::new();
+        for field in fields {
+            writer.push_field(field);
+        }
+        let mut buffer = Cursor::new(Vec::new());
+        writer.write(&mut buffer, false).unwrap();
+        Reader::new().read_raw(buffer.into_inner()).unwrap()
+    }
+
+    fn field(tag: Tag, value: Value) -> Field {
+        Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value,
+        }
+    }
+
+    fn ascii(tag: Tag, value: &str) -> Field {
+        field(tag, Value::Ascii(vec![value.as_bytes().to_vec()]))
+    }
+
+    fn dms(degrees: u32, minutes: u32, seconds: u32) -> Value {
+        Value::Rational(
+            [degrees, minutes, seconds]
+                .into_iter()
+                .map(|num| Rational { num, denom: 1 })
+                .collect(),
+        )
+    }
+
+    #[wasm_bindgen_test]
+    fn parse_reads_all_the_fields() {
+        let metadata = parse(&exif(&[
+            ascii(Tag::DateTimeOriginal, "2023:07:14 18:30:05"),
+            ascii(Tag::DateTime, "2023:08:01 10:00:00"),
+            ascii(Tag::Make, "Canon"),
+            ascii(Tag::Model, "Canon EOS 80D"),
+            field(Tag::PixelXDimension, Value::Long(vec![6000])),
+            field(Tag::PixelYDimension, Value::Long(vec![4000])),
+            field(Tag::GPSLatitude, dms(48, 51, 36)),
+            ascii(Tag::GPSLatitudeRef, "N"),
+            field(Tag::GPSLongitude, dms(2, 21, 0)),
+            ascii(Tag::GPSLongitudeRef, "E"),
+        ]));
+
+        assert_eq!(metadata.taken_at.as_deref(), Some("2023-07-14T18:30:05"));
+        assert_eq!(metadata.camera.as_deref(), Some("Canon EOS 80D"));
+        assert_eq!(metadata.width, Some(6000));
+        assert_eq!(metadata.height, Some(4000));
+        let gps = metadata.gps.unwrap();
+        assert!((gps.lat - 48.86).abs() < 1e-9);
+        assert!((gps.lon - 2.35).abs() < 1e-9);
+    }
+
+    #[wasm_bindgen_test]
+    fn parse_falls_back_and_signs_the_coordinates() {
+        let metadata = parse(&exif(&[
+            ascii(Tag::DateTime, "2023:08:01 10:00:00"),
+            ascii(Tag::Make, "FUJIFILM"),
+            ascii(Tag::Model, "X-T4"),
+            field(Tag::GPSLatitude, dms(33, 52, 12)),
+            ascii(Tag::GPSLatitudeRef, "S"),
+            field(Tag::GPSLongitude, dms(151, 12, 36)),
+            ascii(Tag::GPSLongitudeRef, "W"),
+        ]));
+
+        assert_eq!(metadata.taken_at.as_deref(), Some("2023-08-01T10:00:00"));
+        assert_eq!(metadata.camera.as_deref(), Some("FUJIFILM X-T4"));
+        let gps = metadata.gps.unwrap();
+        assert!((gps.lat + 33.87).abs() < 1e-9);
+        assert!((gps.lon + 151.21).abs() < 1e-9);
+    }
+
+    #[wasm_bindgen_test]
+    fn parse_ignores_the_missing_fields() {
+        let metadata = parse(&exif(&[
+            ascii(Tag::Model, "Pixel 7"),
+            field(Tag::GPSLatitude, dms(48, 51, 36)),
+        ]));
+
+        assert_eq!(metadata.taken_at, None);
+        assert_eq!(metadata.camera.as_deref(), Some("Pixel 7"));
+        assert_eq!(metadata.width, None);
+        // No longitude
+        assert!(metadata.gps.is_none());
+    }
+}
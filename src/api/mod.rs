@@ -1,2 +1,11 @@
 pub mod albumapi;
+pub mod backend;
+pub mod configapi;
+pub mod error;
+pub mod export;
+pub mod http;
+pub mod imaging;
+pub mod local;
+pub mod loginapi;
+pub mod metadata;
 pub mod sharingapi;
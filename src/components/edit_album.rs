@@ -3,16 +3,25 @@ use seed::{self, prelude::*, *};
 use uuid::Uuid;
 
 use crate::{
-    api::albumapi,
-    components::group,
+    api::{albumapi, error::ApiError},
+    components::{
+        group,
+        import_trip::{self, Cluster},
+        notification::notify_api_error,
+        upload,
+    },
     models::{
         album::Album,
         caption::{Color, Style, COLORS},
-        group::Group,
+        draft::Draft,
+        group::{Group, SortBy},
         group_update::{GroupUpdate, UpdateType},
+        history::History,
         notif::{Notif, TypeNotifs},
-        page::{TITLE_EDIT_ALBUM, TITLE_NEW_ALBUM},
+        page::{LK_EDIT_ALBUM, TITLE_EDIT_ALBUM, TITLE_NEW_ALBUM},
+        picture::Picture,
         state::{DeleteStatus, State},
+        upload::{Upload, UploadStatus},
     },
 };
 
@@ -21,18 +30,73 @@ use crate::{
 // ------ -----
 pub struct Model {
     is_new: bool,
-    auth_header: String,
     album: Album,
-    id_pic_drag: String,
+    pic_drag: Option<(Uuid, String)>,
+    drop_target: Option<(Uuid, usize)>,
+    group_drag: Option<Uuid>,
+    group_drop_target: Option<Uuid>,
+    selection: Option<(Uuid, group::Selection)>,
+    // Groups to move to a new album
+    split_groups: Vec<Uuid>,
+    is_splitting: bool,
+    import_trip: import_trip::Model,
+    uploads: upload::Model,
+    history: History,
+    // Public ids of the pictures stored on Cloudinary, to destroy the removed ones on save
+    known_pictures: Vec<String>,
+    // Public ids of the pictures uploaded since the editor was opened, destroyed on discard when not saved
+    session_uploads: Vec<String>,
+    key_stream: Option<StreamHandle>,
+    unload_stream: Option<StreamHandle>,
+    save_status: SaveStatus,
+    // Incremented on each change, to know if the album changed while it was saved
+    revision: u32,
+    autosave_timer: Option<CmdHandle>,
+    // Found in localStorage when the editor is opened
+    draft: Option<Draft>,
+    // Key of the draft of this editor
+    draft_id: String,
+    // Last saved state, base of the merge on conflict
+    base: Album,
+    // Saved by someone else since the base
+    conflict: Option<Album>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum SaveStatus {
+    Saved,
+    Saving,
+    Unsaved,
 }
 
+const AUTOSAVE_DELAY: u32 = 3000;
+
 impl Model {
     pub const fn new() -> Self {
         Self {
             is_new: true,
-            auth_header: String::new(),
             album: Album::new(),
-            id_pic_drag: String::new(),
+            pic_drag: None,
+            drop_target: None,
+            group_drag: None,
+            group_drop_target: None,
+            selection: None,
+            split_groups: Vec::new(),
+            is_splitting: false,
+            import_trip: import_trip::Model::new(),
+            uploads: upload::Model::new(),
+            history: History::new(),
+            known_pictures: Vec::new(),
+            session_uploads: Vec::new(),
+            key_stream: None,
+            unload_stream: None,
+            save_status: SaveStatus::Saved,
+            revision: 0,
+            autosave_timer: None,
+            draft: None,
+            draft_id: String::new(),
+            base: Album::new(),
+            conflict: None,
         }
     }
     pub fn is_not_valid(&self) -> bool {
@@ -42,85 +106,225 @@ impl Model {
         let groups = self.album.groups.clone().unwrap_or_default();
         return groups.iter().any(|g| g.title.is_empty());
     }
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.save_status != SaveStatus::Saved
+    }
 }
 
 // ------ ------
 //    Update
 // ------ ------
 pub enum Msg {
-    SetAuth(String),
     InitComp(Option<String>),
     GetAlbum(String),
-    ErrorGet,
+    ErrorGet(ApiError),
     Received(Album),
     Submit,
+    AutoSave,
     TitleChanged(String),
     StyleChanged(Style),
     ColorChanged(Color),
     AddGroup,
     Group(group::Msg),
-    NotifySuccess(String),
-    NotifyError,
+    NotifySuccess(Saved),
+    NotifyError(ApiError),
     DeleteGroup(Uuid),
-    ErrorDeleteOnePic,
-    SuccessDeleteOnePic(Uuid),
+    DeleteSelected(Uuid, String),
+    MoveSelected(Uuid, Uuid, String),
+    ErrorDeleteOnePic(ApiError),
+    ImportTrip(import_trip::Msg),
+    ToggleSplitGroup(Uuid),
+    Split,
+    SplitDone(String),
+    SplitError(ApiError),
+    Undo,
+    Redo,
+    KeyDown(web_sys::KeyboardEvent),
+    BeforeUnload(web_sys::BeforeUnloadEvent),
+    ConflictReceived(Album),
+    Reload,
+    Overwrite,
+    Merge,
+    RestoreDraft,
+    DiscardDraft,
+    Discard,
+    Exit,
+}
+
+pub struct Saved {
+    // As stored, with its id and new version
+    album: Album,
+    revision: u32,
+    is_manual: bool,
 }
 
 pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::SetAuth(auth_header) => model.auth_header = auth_header,
-        Msg::InitComp(id_opt) => match id_opt {
-            Some(id) => {
-                model.is_new = false;
-                orders.send_msg(Msg::GetAlbum(id));
+        Msg::InitComp(id_opt) => {
+            model.history.clear();
+            model.known_pictures.clear();
+            model.session_uploads.clear();
+            model.selection = None;
+            model.split_groups.clear();
+            model.is_splitting = false;
+            model.save_status = SaveStatus::Saved;
+            model.autosave_timer = None;
+            model.draft = None;
+            model.conflict = None;
+            model.base = Album::new();
+            model.unload_stream = Some(
+                orders.stream_with_handle(streams::window_event(Ev::BeforeUnload, |event| {
+                    Msg::BeforeUnload(event.unchecked_into())
+                })),
+            );
+            model.key_stream = Some(
+                orders.stream_with_handle(streams::window_event(Ev::KeyDown, |event| {
+                    Msg::KeyDown(event.unchecked_into())
+                })),
+            );
+            match id_opt {
+                Some(id) => {
+                    model.is_new = false;
+                    model.draft_id = id.clone();
+                    orders.send_msg(Msg::GetAlbum(id));
+                }
+                None => {
+                    model.album = Album::new();
+                    model.draft_id = Draft::new_id();
+                    model.draft = Draft::load_new();
+                }
             }
-            None => {
-                model.album = Album::new();
+        }
+        Msg::Exit => {
+            if model.has_unsaved_changes() {
+                leave(model, orders);
             }
-        },
+            destroy_removed_pictures(model, orders);
+            model.session_uploads.clear();
+            model.uploads.clear();
+            model.key_stream = None;
+            model.unload_stream = None;
+            model.autosave_timer = None;
+        }
         Msg::GetAlbum(id) => {
             orders.skip(); // No need to rerender
-            let auth = model.auth_header.clone();
             orders.perform_cmd(async {
-                let opt_album = albumapi::get_album(Some(id), None, auth).await;
-                opt_album.map_or(Msg::ErrorGet, Msg::Received)
+                let album_res = albumapi::get_album(Some(id), None).await;
+                album_res.map_or_else(Msg::ErrorGet, Msg::Received)
             });
         }
-        Msg::ErrorGet => {
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Error getting album".to_string(),
-            });
+        Msg::ErrorGet(error) => {
+            notify_api_error(orders, "Error getting album", &error);
         }
         Msg::Received(album) => {
+            model.known_pictures = album.public_ids();
+            model.history.clear();
+            model.save_status = SaveStatus::Saved;
+            model.draft = Draft::load(&album.id);
+            model.base = album.clone();
             model.album = album;
         }
-        Msg::Submit => {
-            orders.skip(); // No need to rerender
-            let auth = model.auth_header.clone();
-            let album = model.album.clone();
+        Msg::Submit => save(model, true, orders),
+        Msg::AutoSave => {
+            model.autosave_timer = None;
+            if model.is_not_valid()
+                || model.save_status == SaveStatus::Saving
+                || model.conflict.is_some()
+                || model.is_splitting
+            {
+                orders.skip(); // No need to rerender
+            } else {
+                save(model, false, orders);
+            }
+        }
+        Msg::NotifySuccess(saved) => {
+            if model.album.id.is_empty() {
+                // The draft now follows the album id
+                Draft::clear(&model.draft_id);
+                model.draft_id = saved.album.id.clone();
+            }
+            model.album.id = saved.album.id.clone();
+            model.album.version = saved.album.version;
+            model.base = saved.album;
+            if saved.revision == model.revision {
+                model.save_status = SaveStatus::Saved;
+                Draft::clear(&model.draft_id);
+            } else {
+                model.save_status = SaveStatus::Unsaved;
+                schedule_autosave(model, orders);
+            }
+            if saved.is_manual {
+                orders.notify(Notif {
+                    notif_type: TypeNotifs::Success,
+                    message: "Album saved".to_string(),
+                });
+            }
+        }
+        Msg::NotifyError(ApiError::Conflict) => {
+            model.save_status = SaveStatus::Unsaved;
+            let id = model.album.id.clone();
             orders.perform_cmd(async {
-                let opt_id = albumapi::update_album(album, auth).await;
-                opt_id.map_or(Msg::NotifyError, Msg::NotifySuccess)
+                let album_res = albumapi::get_album(Some(id), None).await;
+                album_res.map_or_else(Msg::ErrorGet, Msg::ConflictReceived)
             });
         }
-        Msg::NotifySuccess(id) => {
-            model.album.id = id;
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Success,
-                message: "Album saved".to_string(),
-            });
+        Msg::NotifyError(error) => {
+            model.save_status = SaveStatus::Unsaved;
+            notify_api_error(orders, "Error saving album", &error);
         }
-        Msg::NotifyError => {
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Login error".to_string(),
-            });
+        Msg::ConflictReceived(remote) => {
+            model.autosave_timer = None;
+            model.conflict = Some(remote);
+        }
+        Msg::Reload => {
+            if let Some(remote) = model.conflict.take() {
+                model.history.record(&model.album, None);
+                model.known_pictures.extend(remote.public_ids());
+                restore(model, remote.clone());
+                model.base = remote;
+                model.save_status = SaveStatus::Saved;
+                Draft::clear(&model.draft_id);
+            }
+        }
+        Msg::Overwrite => {
+            if let Some(remote) = model.conflict.take() {
+                model.album.version = remote.version;
+                model.base = remote;
+                save(model, true, orders);
+            }
+        }
+        Msg::Merge => {
+            if let Some(remote) = model.conflict.take() {
+                model.history.record(&model.album, None);
+                model.known_pictures.extend(remote.public_ids());
+                let (merged, removed_remotely) = Album::merge(&model.base, &model.album, &remote);
+                for title in removed_remotely {
+                    orders.notify(Notif {
+                        notif_type: TypeNotifs::Error,
+                        message: format!(
+                            "Group \"{title}\" was deleted by someone else, it is kept with your changes"
+                        ),
+                    });
+                }
+                restore(model, merged);
+                model.base = remote;
+                mark_changed(model, orders);
+            }
+        }
+        Msg::TitleChanged(title) => {
+            record(model, orders, Some("title".to_string()));
+            model.album.title = title;
+        }
+        Msg::StyleChanged(style) => {
+            record(model, orders, None);
+            model.album.caption_style = style;
+        }
+        Msg::ColorChanged(color) => {
+            record(model, orders, None);
+            model.album.caption_color = color;
         }
-        Msg::TitleChanged(title) => model.album.title = title,
-        Msg::StyleChanged(style) => model.album.caption_style = style,
-        Msg::ColorChanged(color) => model.album.caption_color = color,
         Msg::AddGroup => {
+            record(model, orders, None);
             if let Some(groups) = &mut model.album.groups {
                 groups.push(Group::new());
             }
@@ -128,76 +332,599 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         Msg::Group(msg) => {
             match msg {
                 group::Msg::UpdateGroup(ref group_update) => {
+                    record_group_update(model, group_update, orders);
                     update_group(group_update, &mut model.album, orders);
                 }
-                group::Msg::DragEnded(ref id_pic_drag) => {
-                    model.id_pic_drag = id_pic_drag.clone();
+                group::Msg::DragStarted(group_id, ref asset_id) => {
+                    model.pic_drag = Some((group_id, asset_id.clone()));
+                }
+                group::Msg::DragOver(group_id, index) => {
+                    if model.group_drag.is_some() {
+                        group_drag_over(model, group_id, orders);
+                    } else if model.drop_target == Some((group_id, index)) {
+                        orders.skip(); // Fired continuously while hovering
+                    } else {
+                        model.drop_target = Some((group_id, index));
+                    }
+                }
+                group::Msg::Drop(group_id, index) => {
+                    if model.group_drag.is_some() {
+                        record(model, orders, None);
+                        drop_group(model, group_id);
+                    } else if model.pic_drag.is_some() {
+                        record(model, orders, None);
+                        drop_pic(model, group_id, index);
+                    }
+                }
+                group::Msg::DragEnded => {
+                    model.pic_drag = None;
+                    model.drop_target = None;
+                    model.group_drag = None;
+                    model.group_drop_target = None;
+                }
+                group::Msg::GroupDragStarted(group_id) => {
+                    model.group_drag = Some(group_id);
+                }
+                group::Msg::GroupDragOver(group_id) => {
+                    group_drag_over(model, group_id, orders);
+                }
+                group::Msg::GroupDrop(group_id) => {
+                    if model.group_drag.is_some() {
+                        record(model, orders, None);
+                        drop_group(model, group_id);
+                    }
+                }
+                group::Msg::MoveUp(group_id) => {
+                    record(model, orders, None);
+                    move_group(model, group_id, -1);
+                }
+                group::Msg::MoveDown(group_id) => {
+                    record(model, orders, None);
+                    move_group(model, group_id, 1);
+                }
+                group::Msg::SelectPicture(group_id, ref asset_id, is_range) => {
+                    select_picture(model, group_id, asset_id, is_range);
+                }
+                group::Msg::AskDeletePictures(group_id, ref asset_id) => {
+                    if let Some(asset_id) = asset_id {
+                        model.selection = Some((group_id, group::Selection::default()));
+                        select_picture(model, group_id, asset_id, false);
+                    }
+                    if let Some((_, selection)) = &mut model.selection {
+                        selection.state = Some(State {
+                            delete_status: DeleteStatus::AskDelete,
+                            total: selection.asset_ids.len(),
+                            current: 0,
+                        });
+                    }
                 }
-                group::Msg::Drop(group_id, ref id_pic_drop) => {
-                    drop_pic(model, group_id, id_pic_drop);
+                group::Msg::CancelDeletePictures => {
+                    if let Some((_, selection)) = &mut model.selection {
+                        selection.state = None;
+                    }
+                }
+                group::Msg::DeleteSelection(group_id) => {
+                    record(model, orders, None);
+                    for asset_id in start_batch(model, DeleteStatus::Deleting) {
+                        orders.send_msg(Msg::DeleteSelected(group_id, asset_id));
+                    }
+                }
+                group::Msg::MoveSelection(group_id, to_group_id) => {
+                    record(model, orders, None);
+                    for asset_id in start_batch(model, DeleteStatus::Moving) {
+                        orders.send_msg(Msg::MoveSelected(group_id, to_group_id, asset_id));
+                    }
+                }
+                group::Msg::ClearSelection => model.selection = None,
+                group::Msg::SetCover(ref asset_id) => {
+                    record(model, orders, None);
+                    model.album.cover = Some(asset_id.clone());
                 }
                 _ => (),
             }
-            group::update(msg, &mut orders.proxy(Msg::Group));
-        }
-        Msg::DeleteGroup(id) => delete_group(model, orders, id),
-        Msg::SuccessDeleteOnePic(group_id) => {
-            let group_update = GroupUpdate {
-                upd_type: UpdateType::DeleteState,
-                id: group_id,
-                picture: None,
-                grp_data: None,
-                count_fake_pictures: None,
-                asset_id: None,
-                caption: None,
-                delete_status: Some(DeleteStatus::Deleting),
-                trip: None,
-            };
-            update_group(&group_update, &mut model.album, orders);
-        }
-        Msg::ErrorDeleteOnePic => {
-            error!("Error deleting picture");
+            group::update(msg, &mut model.uploads, &mut orders.proxy(Msg::Group));
+        }
+        Msg::ImportTrip(msg) => {
+            if let import_trip::Msg::Clustered(ref clusters) = msg {
+                record(model, orders, None);
+                import_clusters(model, clusters, orders);
+            }
+            import_trip::update(
+                msg,
+                &mut model.import_trip,
+                &mut orders.proxy(Msg::ImportTrip),
+            );
+        }
+        Msg::DeleteGroup(id) => delete_group(model, id, orders),
+        // The pictures are destroyed when leaving the editor
+        Msg::DeleteSelected(group_id, asset_id) => {
+            take_picture(&mut model.album, group_id, &asset_id);
+            batch_progress(model);
+        }
+        Msg::MoveSelected(group_id, to_group_id, asset_id) => {
+            if let Some((_, picture)) = take_picture(&mut model.album, group_id, &asset_id) {
+                if let Some(group) = model
+                    .album
+                    .groups
+                    .iter_mut()
+                    .flatten()
+                    .find(|g| g.id == to_group_id)
+                {
+                    group.pictures.get_or_insert_with(Vec::new).push(picture);
+                    if let Some(sort_by) = group.auto_sort {
+                        group.sort_pictures(sort_by);
+                    }
+                }
+            }
+            batch_progress(model);
+        }
+        Msg::ToggleSplitGroup(group_id) => {
+            if let Some(pos) = model.split_groups.iter().position(|id| *id == group_id) {
+                model.split_groups.remove(pos);
+            } else {
+                model.split_groups.push(group_id);
+            }
+        }
+        // Done on the saved album, which is then reloaded
+        Msg::Split => {
+            model.is_splitting = true;
+            let id = model.album.id.clone();
+            let group_ids = model.split_groups.clone();
+            orders.perform_cmd(async {
+                let id_res = albumapi::split_album(id, group_ids).await;
+                id_res.map_or_else(Msg::SplitError, Msg::SplitDone)
+            });
+        }
+        Msg::SplitDone(split_id) => {
+            model.is_splitting = false;
+            model.split_groups.clear();
+            let base_url = window().location().origin().unwrap_or_default();
+            orders.notify(Notif {
+                notif_type: TypeNotifs::Success,
+                message: format!(
+                    "Groups moved to a new album : {base_url}/{LK_EDIT_ALBUM}/{split_id}"
+                ),
+            });
+            // The album is reloaded with a new history, the removed pictures can no longer be restored
+            destroy_removed_pictures(model, orders);
+            orders.send_msg(Msg::GetAlbum(model.album.id.clone()));
+        }
+        Msg::SplitError(error) => {
+            model.is_splitting = false;
+            notify_api_error(orders, "Error splitting album", &error);
+        }
+        Msg::ErrorDeleteOnePic(err) => {
+            error!(format!("Error deleting picture: {err}"));
+        }
+        Msg::Undo => {
+            if let Some(album) = model.history.undo(&model.album) {
+                restore(model, album);
+                mark_changed(model, orders);
+            }
+        }
+        Msg::Redo => {
+            if let Some(album) = model.history.redo(&model.album) {
+                restore(model, album);
+                mark_changed(model, orders);
+            }
+        }
+        Msg::KeyDown(event) => {
+            if (event.ctrl_key() || event.meta_key())
+                && event.key().eq_ignore_ascii_case("z")
+                && !is_typing(&event)
+            {
+                event.prevent_default();
+                if event.shift_key() {
+                    orders.send_msg(Msg::Redo);
+                } else {
+                    orders.send_msg(Msg::Undo);
+                }
+            }
+            orders.skip(); // No need to rerender
+        }
+        Msg::RestoreDraft => {
+            if let Some(draft) = model.draft.take() {
+                // Saved again under the id of this editor
+                Draft::clear(&draft.id);
+                model.history.record(&model.album, None);
+                // Pictures uploaded with the draft are destroyed on leave when removed
+                model.known_pictures.extend(draft.album.public_ids());
+                let id = model.album.id.clone();
+                model.album = draft.album;
+                model.album.id = id;
+                mark_changed(model, orders);
+            }
+        }
+        Msg::DiscardDraft => {
+            if let Some(draft) = model.draft.take() {
+                Draft::clear(&draft.id);
+                // Pictures uploaded with the draft and never saved
+                let current_pictures = model.album.public_ids();
+                let unsaved: Vec<String> = draft
+                    .album
+                    .public_ids()
+                    .into_iter()
+                    .filter(|id| !current_pictures.contains(id))
+                    .collect();
+                destroy_pictures(unsaved, orders);
+            }
+        }
+        Msg::Discard => {
+            if window()
+                .confirm_with_message("Discard your unsaved changes?")
+                .unwrap_or(false)
+            {
+                discard(model, orders);
+                let base = model.base.clone();
+                restore(model, base);
+            }
+        }
+        Msg::BeforeUnload(event) => {
+            // Asks the browser to confirm before leaving
+            if model.has_unsaved_changes() {
+                event.prevent_default();
+                event.set_return_value("");
+            }
+            orders.skip(); // No need to rerender
+        }
+    }
+}
+
+fn save(model: &mut Model, is_manual: bool, orders: &mut impl Orders<Msg>) {
+    model.save_status = SaveStatus::Saving;
+    model.autosave_timer = None;
+    let album = model.album.clone();
+    let revision = model.revision;
+    orders.perform_cmd(async move {
+        let album_res = albumapi::update_album(album).await;
+        album_res.map_or_else(Msg::NotifyError, |album| {
+            Msg::NotifySuccess(Saved {
+                album,
+                revision,
+                is_manual,
+            })
+        })
+    });
+}
+
+// The text fields keep their own undo
+fn is_typing(event: &web_sys::KeyboardEvent) -> bool {
+    event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::HtmlElement>().ok())
+        .map_or(false, |element| {
+            element.is_content_editable()
+                || matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA")
+        })
+}
+
+fn record(model: &mut Model, orders: &mut impl Orders<Msg>, edit: Option<String>) {
+    model.history.record(&model.album, edit);
+    mark_changed(model, orders);
+}
+
+fn mark_changed(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.revision = model.revision.wrapping_add(1);
+    Draft::new(&model.draft_id, model.album.clone()).save();
+    if model.save_status != SaveStatus::Saving {
+        model.save_status = SaveStatus::Unsaved;
+    }
+    schedule_autosave(model, orders);
+}
+
+// The previous timer is cancelled when its handle is dropped
+fn schedule_autosave(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.autosave_timer =
+        Some(orders.perform_cmd_with_handle(cmds::timeout(AUTOSAVE_DELAY, || Msg::AutoSave)));
+}
+
+// Uploads are not undone: their state is kept and their pictures are added to the history
+fn record_group_update(
+    model: &mut Model,
+    group_update: &GroupUpdate,
+    orders: &mut impl Orders<Msg>,
+) {
+    let group_id = group_update.id;
+    let edit = match group_update.upd_type {
+        UpdateType::Title => Some(format!("group-title-{group_id}")),
+        UpdateType::TripChanged => Some(format!("trip-{group_id}")),
+        UpdateType::Caption => Some(format!(
+            "caption-{}",
+            group_update.asset_id.clone().unwrap_or_default()
+        )),
+        UpdateType::Sort | UpdateType::AutoSort => None,
+        UpdateType::AddPicture => {
+            if let Some(picture) = &group_update.picture {
+                add_to_history(model, group_id, picture);
+            }
+            mark_changed(model, orders);
+            return;
+        }
+        UpdateType::AddUploads
+        | UpdateType::UploadStatus
+        | UpdateType::RemoveUpload
+        | UpdateType::ReplaceUpload
+        | UpdateType::DeleteState => return,
+    };
+    record(model, orders, edit);
+}
+
+fn add_to_history(model: &mut Model, group_id: Uuid, picture: &Picture) {
+    model.known_pictures.push(picture.public_id.clone());
+    model.session_uploads.push(picture.public_id.clone());
+    for album in model.history.snapshots_mut() {
+        if let Some(group) = album
+            .groups
+            .as_mut()
+            .and_then(|groups| groups.iter_mut().find(|g| g.id == group_id))
+        {
+            group
+                .pictures
+                .get_or_insert_with(Vec::new)
+                .push(picture.clone());
+        }
+    }
+}
+
+// Shift-click selects the pictures between the last selected one and the clicked one
+fn select_picture(model: &mut Model, group_id: Uuid, asset_id: &str, is_range: bool) {
+    if model.selection.as_ref().map(|(id, _)| *id) != Some(group_id) {
+        model.selection = Some((group_id, group::Selection::default()));
+    }
+    let pictures = model
+        .album
+        .groups
+        .iter()
+        .flatten()
+        .find(|g| g.id == group_id)
+        .and_then(|g| g.pictures.clone())
+        .unwrap_or_default();
+    let Some((_, selection)) = &mut model.selection else {
+        return;
+    };
+    let position = |id: &str| pictures.iter().position(|p| p.asset_id == id);
+    let range = selection
+        .anchor
+        .as_deref()
+        .filter(|_| is_range)
+        .and_then(position)
+        .zip(position(asset_id));
+    if let Some((from, to)) = range {
+        for picture in &pictures[from.min(to)..=from.max(to)] {
+            if !selection.asset_ids.contains(&picture.asset_id) {
+                selection.asset_ids.push(picture.asset_id.clone());
+            }
+        }
+    } else if let Some(pos) = selection.asset_ids.iter().position(|id| id == asset_id) {
+        selection.asset_ids.remove(pos);
+    } else {
+        selection.asset_ids.push(asset_id.to_string());
+    }
+    selection.anchor = Some(asset_id.to_string());
+    selection.state = None;
+    if selection.asset_ids.is_empty() {
+        model.selection = None;
+    }
+}
+
+// The selected pictures are handled one message each, to show the progress
+fn start_batch(model: &mut Model, delete_status: DeleteStatus) -> Vec<String> {
+    let Some((_, selection)) = &mut model.selection else {
+        return Vec::new();
+    };
+    selection.state = Some(State {
+        delete_status,
+        total: selection.asset_ids.len(),
+        current: 0,
+    });
+    selection.asset_ids.clone()
+}
+
+fn batch_progress(model: &mut Model) {
+    if let Some((_, selection)) = &mut model.selection {
+        if let Some(state) = &mut selection.state {
+            state.current += 1;
+            if usize::try_from(state.current).map_or(true, |current| current >= state.total) {
+                model.selection = None;
+            }
+        }
+    }
+}
+
+fn take_picture(album: &mut Album, group_id: Uuid, asset_id: &str) -> Option<(usize, Picture)> {
+    let pictures = album
+        .groups
+        .iter_mut()
+        .flatten()
+        .find(|g| g.id == group_id)
+        .and_then(|group| group.pictures.as_mut())?;
+    let pos = pictures.iter().position(|p| p.asset_id == asset_id)?;
+    Some((pos, pictures.remove(pos)))
+}
+
+fn restore(model: &mut Model, mut album: Album) {
+    // The selected pictures may not be there anymore
+    model.selection = None;
+    if let (Some(groups), Some(current_groups)) = (&mut album.groups, &model.album.groups) {
+        for group in groups {
+            group.uploads = current_groups
+                .iter()
+                .find(|g| g.id == group.id)
+                .map(|g| g.uploads.clone())
+                .unwrap_or_default();
+        }
+    }
+    model.album = album;
+}
+
+// Pictures removed from the album are only destroyed when leaving the editor, so they can be restored until then
+fn destroy_removed_pictures(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let kept_pictures = kept_pictures(model);
+    let removed: Vec<String> = model
+        .known_pictures
+        .drain(..)
+        .filter(|id| !kept_pictures.contains(id))
+        .collect();
+    destroy_pictures(removed, orders);
+}
+
+// Pictures of the saved album, and of the draft kept to be restored later
+fn kept_pictures(model: &Model) -> Vec<String> {
+    let mut kept_pictures = model.base.public_ids();
+    if let Some(draft) = Draft::load(&model.draft_id) {
+        kept_pictures.extend(draft.album.public_ids());
+    }
+    kept_pictures
+}
+
+// Back to the last saved album: pending uploads are aborted and the pictures uploaded since are destroyed
+fn discard(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let saved_pictures = model.base.public_ids();
+    drop_session_uploads(model, &saved_pictures, orders);
+    Draft::clear(&model.draft_id);
+    model.history.clear();
+    model.autosave_timer = None;
+    model.conflict = None;
+    model.save_status = SaveStatus::Saved;
+}
+
+// The draft is kept to be restored later, with the pictures it references
+fn leave(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let kept_pictures = kept_pictures(model);
+    drop_session_uploads(model, &kept_pictures, orders);
+}
+
+// Aborts the pending uploads and destroys the pictures uploaded since the editor was opened
+fn drop_session_uploads(
+    model: &mut Model,
+    kept_pictures: &[String],
+    orders: &mut impl Orders<Msg>,
+) {
+    for group in model.album.groups.iter_mut().flatten() {
+        for upload in group.uploads.drain(..) {
+            upload.abort.abort();
         }
     }
+    let unsaved: Vec<String> = model
+        .session_uploads
+        .drain(..)
+        .filter(|id| !kept_pictures.contains(id))
+        .collect();
+    model.known_pictures.retain(|id| !unsaved.contains(id));
+    destroy_pictures(unsaved, orders);
 }
 
-fn drop_pic(model: &mut Model, group_id: Uuid, id_pic_drop: &str) {
-    let id_pic_drag = &model.id_pic_drag;
+fn destroy_pictures(public_ids: Vec<String>, orders: &mut impl Orders<Msg>) {
+    for public_id in public_ids {
+        orders.perform_cmd(async move {
+            let res = albumapi::delete_picture(public_id).await;
+            res.err().map(Msg::ErrorDeleteOnePic)
+        });
+    }
+}
+
+// Moves the dragged picture before the index, in the same group or another one
+fn drop_pic(model: &mut Model, group_id: Uuid, index: usize) {
+    model.drop_target = None;
+    let Some((drag_group_id, asset_id)) = model.pic_drag.take() else {
+        return;
+    };
+    if !model
+        .album
+        .groups
+        .iter()
+        .flatten()
+        .any(|g| g.id == group_id)
+    {
+        return;
+    }
+    if let Some((pos, picture)) = take_picture(&mut model.album, drag_group_id, &asset_id) {
+        // The index was computed before the picture was removed
+        let index = if drag_group_id == group_id && pos < index {
+            index - 1
+        } else {
+            index
+        };
+        if let Some(group) = model
+            .album
+            .groups
+            .iter_mut()
+            .flatten()
+            .find(|g| g.id == group_id)
+        {
+            let pictures = group.pictures.get_or_insert_with(Vec::new);
+            pictures.insert(index.min(pictures.len()), picture);
+        }
+    }
+}
+
+fn group_drag_over(model: &mut Model, group_id: Uuid, orders: &mut impl Orders<Msg>) {
+    if model.group_drag.is_none() || model.group_drop_target == Some(group_id) {
+        orders.skip(); // Fired continuously while hovering
+    } else {
+        model.group_drop_target = Some(group_id);
+    }
+}
+
+// The dragged group takes the place of the target group
+fn drop_group(model: &mut Model, target_id: Uuid) {
+    model.group_drop_target = None;
+    let Some(drag_id) = model.group_drag.take() else {
+        return;
+    };
     if let Some(groups) = &mut model.album.groups {
-        if let Some(group) = groups.iter_mut().find(|g| g.id == group_id) {
-            if let Some(pictures) = &mut group.pictures {
-                let pos1 = pictures.iter().position(|p| p.asset_id == *id_pic_drag);
-                let pos2 = pictures.iter().position(|p| p.asset_id == id_pic_drop);
-                if let (Some(pos1), Some(pos2)) = (pos1, pos2) {
-                    pictures.swap(pos1, pos2);
+        let from = groups.iter().position(|g| g.id == drag_id);
+        let to = groups.iter().position(|g| g.id == target_id);
+        if let (Some(from), Some(to)) = (from, to) {
+            let group = groups.remove(from);
+            groups.insert(to, group);
+        }
+    }
+}
+
+fn move_group(model: &mut Model, group_id: Uuid, offset: isize) {
+    if let Some(groups) = &mut model.album.groups {
+        if let Some(pos) = groups.iter().position(|g| g.id == group_id) {
+            if let Some(new_pos) = pos.checked_add_signed(offset) {
+                if new_pos < groups.len() {
+                    groups.swap(pos, new_pos);
                 }
             }
         }
     }
 }
 
-fn delete_group(model: &mut Model, orders: &mut impl Orders<Msg>, group_id: Uuid) {
+// One group per cluster, the pictures are uploaded through the group
+fn import_clusters(model: &mut Model, clusters: &[Cluster], orders: &mut impl Orders<Msg>) {
     if let Some(groups) = &mut model.album.groups {
-        if let Some(group) = groups.iter().find(|g| g.id == group_id) {
-            // Delete all pics
-            let pic_ids = group.pictures.clone().map_or_else(Vec::new, |pictures| {
-                pictures.iter().map(|p| p.public_id.clone()).collect()
-            });
-            for pic_id in pic_ids {
-                orders.perform_cmd(async move {
-                    let res = albumapi::delete_picture(pic_id).await;
-                    if res {
-                        Msg::SuccessDeleteOnePic(group_id)
-                    } else {
-                        Msg::ErrorDeleteOnePic
-                    }
-                });
-            }
+        for cluster in clusters {
+            let mut group = Group::new();
+            group.title = cluster.title.clone();
+            group.auto_sort = Some(SortBy::DateTaken);
+            let group_id = group.id;
+            groups.push(group);
+
+            upload::start(
+                cluster.files.clone(),
+                &model.album.id,
+                group_id,
+                &mut orders.proxy(|msg| Msg::Group(group::Msg::Upload(msg))),
+            );
         }
-        if let Some(index) = groups.iter().position(|g| g.id == group_id) {
-            groups.remove(index);
+    }
+}
+
+// The pictures are destroyed when leaving the editor
+fn delete_group(model: &mut Model, group_id: Uuid, orders: &mut impl Orders<Msg>) {
+    if let Some(groups) = &mut model.album.groups {
+        if let Some(group) = groups.iter_mut().find(|g| g.id == group_id) {
+            group.state = None;
         }
     }
+    record(model, orders, None);
+    if let Some(groups) = &mut model.album.groups {
+        groups.retain(|g| g.id != group_id);
+    }
 }
 
 fn update_group(group_update: &GroupUpdate, album: &mut Album, orders: &mut impl Orders<Msg>) {
@@ -208,8 +935,42 @@ fn update_group(group_update: &GroupUpdate, album: &mut Album, orders: &mut impl
                 UpdateType::TripChanged => {
                     group.trip = grp_upd.trip;
                 }
-                UpdateType::CountFakePictures => {
-                    group.count_fake_pictures = grp_upd.count_fake_pictures.unwrap_or_default();
+                UpdateType::AddUploads => {
+                    group.uploads.extend(grp_upd.uploads.unwrap_or_default());
+                }
+                UpdateType::UploadStatus => {
+                    if let Some(upload) = group
+                        .uploads
+                        .iter_mut()
+                        .find(|u| Some(u.id) == grp_upd.upload_id)
+                    {
+                        if let Some(status) = grp_upd.upload_status {
+                            upload.status = status;
+                        }
+                    }
+                }
+                UpdateType::RemoveUpload => {
+                    group.uploads.retain(|u| Some(u.id) != grp_upd.upload_id);
+                }
+                UpdateType::ReplaceUpload => {
+                    for new_upload in grp_upd.uploads.unwrap_or_default() {
+                        if let Some(upload) =
+                            group.uploads.iter_mut().find(|u| u.id == new_upload.id)
+                        {
+                            *upload = new_upload;
+                        }
+                    }
+                }
+                UpdateType::Sort => {
+                    if let Some(sort_by) = grp_upd.sort_by {
+                        group.sort_pictures(sort_by);
+                    }
+                }
+                UpdateType::AutoSort => {
+                    group.auto_sort = grp_upd.sort_by;
+                    if let Some(sort_by) = grp_upd.sort_by {
+                        group.sort_pictures(sort_by);
+                    }
                 }
                 UpdateType::Title => {
                     group.title = grp_upd.grp_data.unwrap_or_default();
@@ -218,7 +979,19 @@ fn update_group(group_update: &GroupUpdate, album: &mut Album, orders: &mut impl
                     let picture = grp_upd.picture.unwrap_or_default();
                     if let Some(pictures) = &mut group.pictures {
                         pictures.push(picture);
-                        group.count_fake_pictures -= 1;
+                    }
+                    if let Some(upload) = group
+                        .uploads
+                        .iter_mut()
+                        .find(|u| Some(u.id) == grp_upd.upload_id)
+                    {
+                        upload.status = UploadStatus::Done;
+                    }
+                    if !group.uploads.iter().any(Upload::is_pending) {
+                        group.uploads.clear();
+                    }
+                    if let Some(sort_by) = group.auto_sort {
+                        group.sort_pictures(sort_by);
                     }
                 }
                 UpdateType::Caption => {
@@ -231,15 +1004,6 @@ fn update_group(group_update: &GroupUpdate, album: &mut Album, orders: &mut impl
                         }
                     }
                 }
-                UpdateType::DeletePicture => {
-                    if let Some(pictures) = &mut group.pictures {
-                        if let Some(pos) = pictures.iter().position(|p| {
-                            p.asset_id == grp_upd.clone().asset_id.unwrap_or_default()
-                        }) {
-                            pictures.remove(pos);
-                        }
-                    }
-                }
                 UpdateType::DeleteState => {
                     if let Some(del_state) = &group_update.delete_status {
                         let mut total = 0;
@@ -260,7 +1024,7 @@ fn update_group(group_update: &GroupUpdate, album: &mut Album, orders: &mut impl
                                     current,
                                 });
                             }
-                            DeleteStatus::AskDelete => (),
+                            DeleteStatus::AskDelete | DeleteStatus::Moving => (),
                         }
                     }
                 }
@@ -313,17 +1077,82 @@ pub fn view(model: &Model) -> Node<Msg> {
                         ev(Ev::Click, |_| Msg::Submit),
                         attrs! { At::Disabled => model.is_not_valid().as_at_value() },
                     ]
+                ],
+                div![
+                    C!("control"),
+                    button![
+                        C!["button", "is-light", "is-small"],
+                        attrs! {
+                            At::Title => "Undo (Ctrl+Z)",
+                            At::Disabled => (!model.history.can_undo()).as_at_value(),
+                        },
+                        span![C!("icon"), i![C!("ion-reply")]],
+                        ev(Ev::Click, |_| Msg::Undo),
+                    ]
+                ],
+                div![
+                    C!("control"),
+                    button![
+                        C!["button", "is-light", "is-small"],
+                        attrs! {
+                            At::Title => "Redo (Ctrl+Shift+Z)",
+                            At::Disabled => (!model.history.can_redo()).as_at_value(),
+                        },
+                        span![C!("icon"), i![C!("ion-forward")]],
+                        ev(Ev::Click, |_| Msg::Redo),
+                    ]
+                ],
+                div![
+                    C!("control"),
+                    button![
+                        C!["button", "is-danger", "is-light", "is-small"],
+                        attrs! {
+                            At::Title => "Discard changes",
+                            At::Disabled => (!model.has_unsaved_changes()).as_at_value(),
+                        },
+                        span![C!("icon"), i![C!("ion-close")]],
+                        ev(Ev::Click, |_| Msg::Discard),
+                    ]
                 ]
             ],
+            model.draft.as_ref().map_or(empty!(), view_draft),
+            model
+                .conflict
+                .as_ref()
+                .map_or(empty!(), |remote| view_conflict(&model.base, remote)),
+            view_save_status(model.save_status),
             caption_view(model),
         ],
-        &model
-            .album
-            .groups
-            .as_ref()
-            .map_or(empty!(), |groups| div![groups.iter().map(|group| {
-                group::view(model.album.id.clone(), group).map_msg(Msg::Group)
-            })],),
+        &model.album.groups.as_ref().map_or(empty!(), |groups| {
+            let targets: Vec<(Uuid, String)> =
+                groups.iter().map(|g| (g.id, g.title.clone())).collect();
+            div![groups.iter().enumerate().map(|(index, group)| {
+                let drag = group::DragState {
+                    is_dragging_picture: model.pic_drag.is_some(),
+                    drop_index: model
+                        .drop_target
+                        .filter(|(group_id, _)| *group_id == group.id)
+                        .map(|(_, index)| index),
+                    is_drop_target: model.group_drop_target == Some(group.id),
+                };
+                let selection = model
+                    .selection
+                    .as_ref()
+                    .filter(|(group_id, _)| *group_id == group.id)
+                    .map(|(_, selection)| selection);
+                let is_last = index + 1 == groups.len();
+                group::view(
+                    &model.album,
+                    group,
+                    index == 0,
+                    is_last,
+                    &drag,
+                    selection,
+                    &targets,
+                )
+                .map_msg(Msg::Group)
+            })]
+        }),
         div![
             C!["mt-5"],
             button![
@@ -334,9 +1163,132 @@ pub fn view(model: &Model) -> Node<Msg> {
                 ev(Ev::Click, |_| Msg::AddGroup),
             ],
         ],
+        import_trip::view(&model.import_trip, model.album.id.is_empty()).map_msg(Msg::ImportTrip),
+        view_split(model),
     ]
 }
 
+// At least one group stays, the album must be saved as the split is done on the server
+fn view_split(model: &Model) -> Node<Msg> {
+    let groups = model.album.groups.as_deref().unwrap_or_default();
+    if model.is_new || groups.len() < 2 {
+        return empty!();
+    }
+    let split_count = groups
+        .iter()
+        .filter(|g| model.split_groups.contains(&g.id))
+        .count();
+    let can_split = split_count > 0 && split_count < groups.len() && !model.has_unsaved_changes();
+    div![
+        C!["box", "mt-5"],
+        p![C!["title", "is-6", "has-text-link"], "Split album"],
+        p![
+            C!["is-size-7", "mb-2"],
+            "Move the selected groups to a new album"
+        ],
+        groups.iter().map(|group| {
+            let group_id = group.id;
+            label![
+                C!["checkbox", "is-size-7", "mr-3"],
+                input![
+                    C!("mr-1"),
+                    attrs! {
+                        At::Type => "checkbox",
+                        At::Checked => model.split_groups.contains(&group.id).as_at_value(),
+                    },
+                    ev(Ev::Change, move |_| Msg::ToggleSplitGroup(group_id)),
+                ],
+                &group.title
+            ]
+        }),
+        div![
+            C!("mt-2"),
+            button![
+                C![
+                    "button",
+                    "is-link",
+                    "is-light",
+                    "is-small",
+                    IF!(model.is_splitting => "is-loading")
+                ],
+                attrs! {
+                    At::Title => if model.has_unsaved_changes() { "Save the album first" } else { "Split" },
+                    At::Disabled => (!can_split || model.is_splitting).as_at_value(),
+                },
+                span!["Move to a new album"],
+                ev(Ev::Click, |_| Msg::Split),
+            ]
+        ]
+    ]
+}
+
+fn view_draft(draft: &Draft) -> Node<Msg> {
+    div![
+        C!["notification", "is-warning", "is-light", "p-3"],
+        p![format!(
+            "An unsaved draft from {} was found.",
+            draft.saved_at_label()
+        )],
+        div![
+            C!["buttons", "are-small", "mt-2"],
+            button![
+                C!["button", "is-warning"],
+                "Restore",
+                ev(Ev::Click, |_| Msg::RestoreDraft),
+            ],
+            button![
+                C!["button", "is-light"],
+                "Discard",
+                ev(Ev::Click, |_| Msg::DiscardDraft),
+            ],
+        ]
+    ]
+}
+
+fn view_conflict(base: &Album, remote: &Album) -> Node<Msg> {
+    let updated_at = remote.updated_at.clone().unwrap_or_default();
+    div![
+        C!["notification", "is-danger", "is-light", "p-3"],
+        p![format!(
+            "This album was changed by someone else {updated_at}"
+        )],
+        ul![
+            C!["is-size-7", "my-2"],
+            remote.changes(base).iter().map(|change| li![change])
+        ],
+        div![
+            C!["buttons", "are-small"],
+            button![
+                C!["button", "is-link"],
+                "Merge",
+                attrs! { At::Title => "Keep both changes, yours win on the same group" },
+                ev(Ev::Click, |_| Msg::Merge),
+            ],
+            button![
+                C!["button", "is-light"],
+                "Reload",
+                attrs! { At::Title => "Discard your changes" },
+                ev(Ev::Click, |_| Msg::Reload),
+            ],
+            button![
+                C!["button", "is-danger", "is-light"],
+                "Overwrite",
+                attrs! { At::Title => "Discard their changes" },
+                ev(Ev::Click, |_| Msg::Overwrite),
+            ],
+        ]
+    ]
+}
+
+fn view_save_status(save_status: SaveStatus) -> Node<Msg> {
+    let (c_status, label) = match save_status {
+        SaveStatus::Saved => ("is-success", "Saved"),
+        SaveStatus::Saving => ("is-info", "Saving…"),
+        SaveStatus::Unsaved => ("is-warning", "Unsaved"),
+    };
+    div![C!["mb-3"], span![C!["tag", "is-light", c_status], label]]
+}
+
 fn caption_view(model: &Model) -> Node<Msg> {
     let (colors_1, colors_2) = COLORS.split_at(10);
 
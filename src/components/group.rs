@@ -1,11 +1,12 @@
 use super::picture;
 use super::upload;
-use crate::models::state::DeleteStatus;
+use crate::models::state::{DeleteStatus, State};
 use crate::models::trip::TranspMode;
 use crate::models::trip::Trip;
 use crate::models::trip::TRANSP_MODE;
 use crate::models::{
-    group::Group,
+    album::Album,
+    group::{Group, SortBy, SORT_BY},
     group_update::{GroupUpdate, UpdateType},
 };
 use seed::{self, prelude::*, *};
@@ -20,71 +21,93 @@ pub enum Msg {
     Upload(upload::Msg),
     Picture(picture::Msg),
     BeginDeleteGroup(Uuid),
-    Drop(Uuid, String),
-    DragEnded(String),
-    DragOver,
+    DragStarted(Uuid, String),
+    DragOver(Uuid, usize),
+    Drop(Uuid, usize),
+    DragEnded,
+    GroupDragStarted(Uuid),
+    GroupDragOver(Uuid),
+    GroupDrop(Uuid),
+    MoveUp(Uuid),
+    MoveDown(Uuid),
     TripChanged(Uuid, Option<TranspMode>, String, String),
+    SortPictures(Uuid, SortBy),
+    AutoSortChanged(Uuid, Option<SortBy>),
+    SelectPicture(Uuid, String, bool),
+    // Asks to delete one picture, or the selection when none
+    AskDeletePictures(Uuid, Option<String>),
+    CancelDeletePictures,
+    DeleteSelection(Uuid),
+    MoveSelection(Uuid, Uuid),
+    ClearSelection,
+    SetCover(String),
 }
 
-pub fn update(msg: Msg, orders: &mut impl Orders<Msg>) {
+pub fn update(msg: Msg, uploads: &mut upload::Model, orders: &mut impl Orders<Msg>) {
     match msg {
         Msg::TitleChanged(input, group_id) => {
             orders.send_msg(Msg::UpdateGroup(GroupUpdate {
                 upd_type: UpdateType::Title,
                 id: group_id,
-                picture: None,
                 grp_data: Some(input),
-                count_fake_pictures: None,
-                asset_id: None,
-                caption: None,
-                delete_status: None,
-                trip: None,
+                ..Default::default()
             }));
         }
         Msg::Upload(msg) => {
             match msg {
-                upload::Msg::Success(ref picture, group_id) => {
+                upload::Msg::Success(ref picture, group_id, upload_id) => {
                     orders.send_msg(Msg::UpdateGroup(GroupUpdate {
                         upd_type: UpdateType::AddPicture,
                         id: group_id,
                         picture: Some(picture.clone()),
-                        grp_data: None,
-                        count_fake_pictures: None,
-                        asset_id: None,
-                        caption: None,
-                        delete_status: None,
-                        trip: None,
+                        upload_id: Some(upload_id),
+                        ..Default::default()
                     }));
                 }
-                upload::Msg::RenderFakePictures(count, group_id) => {
+                upload::Msg::AddUploads(ref uploads, group_id) => {
                     orders.send_msg(Msg::UpdateGroup(GroupUpdate {
-                        upd_type: UpdateType::CountFakePictures,
+                        upd_type: UpdateType::AddUploads,
                         id: group_id,
-                        picture: None,
-                        grp_data: None,
-                        count_fake_pictures: Some(count),
-                        asset_id: None,
-                        caption: None,
-                        delete_status: None,
-                        trip: None,
+                        uploads: Some(uploads.clone()),
+                        ..Default::default()
+                    }));
+                }
+                upload::Msg::Progress(group_id, upload_id, ref status) => {
+                    orders.send_msg(Msg::UpdateGroup(GroupUpdate {
+                        upd_type: UpdateType::UploadStatus,
+                        id: group_id,
+                        upload_id: Some(upload_id),
+                        upload_status: Some(status.clone()),
+                        ..Default::default()
+                    }));
+                }
+                upload::Msg::Renewed(ref upload, group_id) => {
+                    orders.send_msg(Msg::UpdateGroup(GroupUpdate {
+                        upd_type: UpdateType::ReplaceUpload,
+                        id: group_id,
+                        uploads: Some(vec![upload.clone()]),
+                        ..Default::default()
+                    }));
+                }
+                upload::Msg::Cancel(ref upload, group_id) => {
+                    orders.send_msg(Msg::UpdateGroup(GroupUpdate {
+                        upd_type: UpdateType::RemoveUpload,
+                        id: group_id,
+                        upload_id: Some(upload.id),
+                        ..Default::default()
                     }));
                 }
                 _ => (),
             }
-            upload::update(msg, &mut orders.proxy(Msg::Upload));
+            upload::update(msg, uploads, &mut orders.proxy(Msg::Upload));
         }
-        Msg::Picture(msg) => update_picture(msg, orders),
+        Msg::Picture(msg) => update_picture(&msg, orders),
         Msg::BeginDeleteGroup(group_id) => {
             orders.send_msg(Msg::UpdateGroup(GroupUpdate {
                 upd_type: UpdateType::DeleteState,
                 id: group_id,
-                picture: None,
-                grp_data: None,
-                count_fake_pictures: None,
-                asset_id: None,
-                caption: None,
                 delete_status: Some(DeleteStatus::Deleting),
-                trip: None,
+                ..Default::default()
             }));
         }
         Msg::TripChanged(group_id, transp_mode, origin, destination) => {
@@ -99,56 +122,124 @@ pub fn update(msg: Msg, orders: &mut impl Orders<Msg>) {
             orders.send_msg(Msg::UpdateGroup(GroupUpdate {
                 upd_type: UpdateType::TripChanged,
                 id: group_id,
-                picture: None,
-                grp_data: None,
-                count_fake_pictures: None,
-                asset_id: None,
-                caption: None,
-                delete_status: None,
                 trip,
+                ..Default::default()
+            }));
+        }
+        Msg::SortPictures(group_id, sort_by) => {
+            orders.send_msg(Msg::UpdateGroup(GroupUpdate {
+                upd_type: UpdateType::Sort,
+                id: group_id,
+                sort_by: Some(sort_by),
+                ..Default::default()
+            }));
+        }
+        Msg::AutoSortChanged(group_id, sort_by) => {
+            orders.send_msg(Msg::UpdateGroup(GroupUpdate {
+                upd_type: UpdateType::AutoSort,
+                id: group_id,
+                sort_by,
+                ..Default::default()
             }));
         }
-        Msg::UpdateGroup(_) | Msg::Drop(_, _) | Msg::DragEnded(_) | Msg::DragOver => (),
+        Msg::UpdateGroup(_)
+        | Msg::DragStarted(_, _)
+        | Msg::DragOver(_, _)
+        | Msg::Drop(_, _)
+        | Msg::DragEnded
+        | Msg::GroupDragStarted(_)
+        | Msg::GroupDragOver(_)
+        | Msg::GroupDrop(_)
+        | Msg::MoveUp(_)
+        | Msg::MoveDown(_)
+        | Msg::SelectPicture(_, _, _)
+        | Msg::AskDeletePictures(_, _)
+        | Msg::CancelDeletePictures
+        | Msg::DeleteSelection(_)
+        | Msg::MoveSelection(_, _)
+        | Msg::ClearSelection
+        | Msg::SetCover(_) => (),
     }
 }
 
-fn update_picture(msg: picture::Msg, orders: &mut impl Orders<Msg>) {
+fn update_picture(msg: &picture::Msg, orders: &mut impl Orders<Msg>) {
     match msg {
-        picture::Msg::UpdateCaption(group_id, ref caption, ref asset_id) => {
+        picture::Msg::UpdateCaption(group_id, caption, asset_id) => {
             orders.send_msg(Msg::UpdateGroup(GroupUpdate {
                 upd_type: UpdateType::Caption,
-                id: group_id,
-                picture: None,
-                grp_data: None,
-                count_fake_pictures: None,
+                id: *group_id,
                 asset_id: Some(asset_id.clone()),
                 caption: Some(caption.clone()),
-                delete_status: None,
-                trip: None,
+                ..Default::default()
             }));
         }
-        picture::Msg::DeletePictureSuccess(group_id, ref asset_id) => {
-            orders.send_msg(Msg::UpdateGroup(GroupUpdate {
-                upd_type: UpdateType::DeletePicture,
-                id: group_id,
-                picture: None,
-                grp_data: None,
-                count_fake_pictures: None,
-                asset_id: Some(asset_id.clone()),
-                caption: None,
-                delete_status: None,
-                trip: None,
-            }));
+        picture::Msg::Select(group_id, asset_id, is_range) => {
+            orders.send_msg(Msg::SelectPicture(*group_id, asset_id.clone(), *is_range));
         }
-        _ => (),
+        picture::Msg::AskDelete(group_id, asset_id) => {
+            orders.send_msg(Msg::AskDeletePictures(*group_id, Some(asset_id.clone())));
+        }
+        picture::Msg::CancelDelete => {
+            orders.send_msg(Msg::CancelDeletePictures);
+        }
+        picture::Msg::ConfirmDelete(group_id) => {
+            orders.send_msg(Msg::DeleteSelection(*group_id));
+        }
+        picture::Msg::SetCover(asset_id) => {
+            orders.send_msg(Msg::SetCover(asset_id.clone()));
+        }
+    }
+}
+
+// Drag and drop state of the edit page for one group
+pub struct DragState {
+    pub is_dragging_picture: bool,
+    pub drop_index: Option<usize>,
+    pub is_drop_target: bool,
+}
+
+// Pictures selected in one group of the edit page
+#[derive(Default)]
+pub struct Selection {
+    pub asset_ids: Vec<String>,
+    // Start of the shift-click range
+    pub anchor: Option<String>,
+    // Confirmation, then progress of the batch delete or move
+    pub state: Option<State>,
+}
+
+impl Selection {
+    fn is_asking_delete(&self, asset_id: &str) -> bool {
+        matches!(&self.state, Some(state) if state.delete_status == DeleteStatus::AskDelete)
+            && self.asset_ids == [asset_id]
     }
-    picture::update(msg, &mut orders.proxy(Msg::Picture));
 }
 
-pub fn view(album_id: String, group: &Group) -> Node<Msg> {
+pub fn view(
+    album: &Album,
+    group: &Group,
+    is_first: bool,
+    is_last: bool,
+    drag: &DragState,
+    selection: Option<&Selection>,
+    targets: &[(Uuid, String)],
+) -> Node<Msg> {
     let grp_id = group.id;
+    let album_id = album.id.clone();
+    let cover = album.cover_picture().map(|p| p.asset_id.clone());
+    let drop_index = drag.drop_index;
     div![
-        C!["box group"],
+        C!["box group", IF!(drag.is_drop_target => "is-drop-target")],
+        drag_ev(Ev::DragStart, move |_| Msg::GroupDragStarted(grp_id)),
+        ev(Ev::DragEnd, |_| Msg::DragEnded),
+        drag_ev(Ev::DragOver, move |event| {
+            event.prevent_default();
+            Msg::GroupDragOver(grp_id)
+        }),
+        drag_ev(Ev::Drop, move |event| {
+            event.prevent_default();
+            Msg::GroupDrop(grp_id)
+        }),
         if group.state.is_some() {
             let state = group.state.clone().unwrap();
             progress![
@@ -157,6 +248,29 @@ pub fn view(album_id: String, group: &Group) -> Node<Msg> {
             ]
         } else {
             div![
+                div![
+                    C!["is-flex", "is-align-items-center", "mb-2"],
+                    span![
+                        C!["icon", "group-drag"],
+                        attrs! { At::Draggable => true, At::Title => "Drag to move the group" },
+                        i![C!("ion-drag")],
+                    ],
+                    div![
+                        C!["buttons", "are-small", "ml-2"],
+                        button![
+                            C!["button", "is-light"],
+                            attrs! { At::Disabled => is_first.as_at_value(), At::Title => "Move up" },
+                            span![C!("icon"), i![C!("ion-arrow-up-b")]],
+                            ev(Ev::Click, move |_| Msg::MoveUp(grp_id)),
+                        ],
+                        button![
+                            C!["button", "is-light"],
+                            attrs! { At::Disabled => is_last.as_at_value(), At::Title => "Move down" },
+                            span![C!("icon"), i![C!("ion-arrow-down-b")]],
+                            ev(Ev::Click, move |_| Msg::MoveDown(grp_id)),
+                        ],
+                    ],
+                ],
                 div![
                     C!["label", "control", "field"],
                     "Group name",
@@ -182,41 +296,173 @@ pub fn view(album_id: String, group: &Group) -> Node<Msg> {
                 ],
                 span![C!["label"], "Trip"],
                 view_trip(group),
-                div![
-                    group.pictures.as_ref().map_or(empty![], |pictures| {
-                        div![pictures.iter().map(|picture| {
-                            let asset_id = picture.asset_id.clone();
-                            let asset_id2 = picture.asset_id.clone();
-                            let grp_id = group.id;
-                            div![
-                                ev(Ev::DragEnd, move |_| Msg::DragEnded(asset_id)),
-                                ev(Ev::Drop, move |_| Msg::Drop(grp_id, asset_id2)),
-                                drag_ev(Ev::DragOver, |event| {
-                                    event.stop_propagation();
-                                    event.prevent_default();
-                                    event.data_transfer().unwrap().set_drop_effect("move");
-                                    Msg::DragOver
-                                }),
-                                picture::view(group.id, picture).map_msg(Msg::Picture),
-                            ]
-                        })]
-                    }),
-                    (0..group.count_fake_pictures).map(|_| {
-                        figure![
-                            C!["image", "is-128x128", "m-1"],
-                            progress![
-                                C!["progress", "picture-progress"],
-                                attrs! { At::Max => 100 }
-                            ],
+                view_sort(group),
+                selection.map_or(empty!(), |selection| view_selection(
+                    grp_id, selection, targets
+                )),
+                div![group.pictures.as_ref().map_or(empty![], |pictures| {
+                    div![pictures.iter().enumerate().map(|(index, picture)| {
+                        let asset_id = picture.asset_id.clone();
+                        div![
+                            IF!(drop_index == Some(index) => C!("drop-before")),
+                            drag_ev(Ev::DragStart, move |event| {
+                                event.stop_propagation();
+                                // Firefox needs data to start the drag
+                                if let Some(data_transfer) = event.data_transfer() {
+                                    _ = data_transfer.set_data("text/plain", &asset_id);
+                                }
+                                Msg::DragStarted(grp_id, asset_id)
+                            }),
+                            ev(Ev::DragEnd, |_| Msg::DragEnded),
+                            drop_events(grp_id, index),
+                            picture::view(
+                                group.id,
+                                picture,
+                                selection
+                                    .map_or(false, |s| s.asset_ids.contains(&picture.asset_id)),
+                                selection.map_or(false, |s| s.is_asking_delete(&picture.asset_id)),
+                                cover.as_ref() == Some(&picture.asset_id),
+                            )
+                            .map_msg(Msg::Picture),
                         ]
-                    }),
-                ],
+                    })]
+                }),],
+                IF!(drag.is_dragging_picture || group.pictures.as_ref().map_or(true, Vec::is_empty) => {
+                    let len = group.pictures.as_ref().map_or(0, Vec::len);
+                    div![
+                        C!["group-drop-zone", "is-size-7", "mt-2", IF!(drop_index == Some(len) => "is-active")],
+                        "Drop pictures here",
+                        drop_events(grp_id, len),
+                    ]
+                }),
+                upload::view_uploads(&album_id, group.id, &group.uploads).map_msg(Msg::Upload),
                 upload::view(album_id, group.id).map_msg(Msg::Upload),
             ]
         }
     ]
 }
 
+// Dropping inserts the picture before the given index
+fn drop_events(group_id: Uuid, index: usize) -> Vec<EventHandler<Msg>> {
+    vec![
+        drag_ev(Ev::DragOver, move |event| {
+            event.stop_propagation();
+            event.prevent_default();
+            if let Some(data_transfer) = event.data_transfer() {
+                data_transfer.set_drop_effect("move");
+            }
+            Msg::DragOver(group_id, index)
+        }),
+        drag_ev(Ev::Drop, move |event| {
+            event.stop_propagation();
+            event.prevent_default();
+            Msg::Drop(group_id, index)
+        }),
+    ]
+}
+
+fn view_selection(grp_id: Uuid, selection: &Selection, targets: &[(Uuid, String)]) -> Node<Msg> {
+    let count = selection.asset_ids.len();
+    div![
+        C!["notification", "is-link", "is-light", "p-2", "mt-2"],
+        match &selection.state {
+            Some(state) if state.delete_status != DeleteStatus::AskDelete => progress![
+                C!["progress", "is-danger"],
+                attrs! { At::Value => state.current, At::Max => state.total }
+            ],
+            Some(_) if count > 1 => div![
+                span![C!["mr-2"], format!("Delete these {count} pictures ?")],
+                button![
+                    C!["button", "is-link", "is-light", "is-small", "mr-2"],
+                    span![C!("icon"), i![C!("ion-close-circled")]],
+                    span!["NO"],
+                    ev(Ev::Click, |_| Msg::CancelDeletePictures),
+                ],
+                button![
+                    C!["button", "is-danger", "is-light", "is-small"],
+                    span![C!("icon"), i![C!("ion-close-circled")]],
+                    span!["YES"],
+                    ev(Ev::Click, move |_| Msg::DeleteSelection(grp_id)),
+                ],
+            ],
+            _ => div![
+                C!["is-flex", "is-align-items-center", "is-flex-wrap-wrap"],
+                span![C!["mr-2"], format!("{count} selected")],
+                button![
+                    C!["button", "is-danger", "is-light", "is-small", "mr-2"],
+                    span![C!("icon"), i![C!("ion-close-circled")]],
+                    span!["Delete"],
+                    ev(Ev::Click, move |_| Msg::AskDeletePictures(grp_id, None)),
+                ],
+                div![
+                    C!["select", "is-small", "mr-2"],
+                    select![
+                        option![
+                            attrs! { At::Value => "", At::Selected => true.as_at_value() },
+                            "Move to…"
+                        ],
+                        targets
+                            .iter()
+                            .filter(|(id, _)| *id != grp_id)
+                            .map(|(id, title)| {
+                                option![attrs! { At::Value => id.to_string() }, title]
+                            }),
+                        input_ev(Ev::Change, move |value| {
+                            Uuid::parse_str(&value)
+                                .ok()
+                                .map(|to| Msg::MoveSelection(grp_id, to))
+                        }),
+                    ]
+                ],
+                button![
+                    C!["button", "is-light", "is-small"],
+                    "Clear",
+                    ev(Ev::Click, |_| Msg::ClearSelection),
+                ],
+            ],
+        }
+    ]
+}
+
+fn view_sort(group: &Group) -> Node<Msg> {
+    let grp_id = group.id;
+    div![
+        C!["mt-2"],
+        span![C!["label"], "Sort pictures by"],
+        div![
+            C!["buttons", "are-small"],
+            SORT_BY.iter().map(|&sort_by| {
+                button![
+                    C!["button", "is-link", "is-light"],
+                    sort_by.to_string(),
+                    ev(Ev::Click, move |_| Msg::SortPictures(grp_id, sort_by)),
+                ]
+            }),
+        ],
+        span![C!["label"], "Auto-sort on upload"],
+        div![
+            C!["field", "select", "is-small"],
+            select![
+                option![
+                    "None",
+                    ev(Ev::Click, move |_| Msg::AutoSortChanged(grp_id, None)),
+                    attrs!(At::Selected => (group.auto_sort.is_none()).as_at_value())
+                ],
+                SORT_BY.iter().map(|&sort_by| {
+                    option![
+                        sort_by.to_string(),
+                        ev(Ev::Click, move |_| Msg::AutoSortChanged(
+                            grp_id,
+                            Some(sort_by)
+                        )),
+                        attrs!(At::Selected => (group.auto_sort == Some(sort_by)).as_at_value())
+                    ]
+                }),
+            ]
+        ],
+    ]
+}
+
 fn view_trip(group: &Group) -> Node<Msg> {
     let grp_id = group.id;
     let inp_ori = group.trip.clone().unwrap_or_default().origin;
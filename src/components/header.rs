@@ -37,7 +37,7 @@ impl Model {
 pub enum Msg {
     OpenOrCloseMenu,
     SetPage(Page),
-    SetIsLogged,
+    SetIsLogged(bool),
     ClickLogInOrOut,
     LogInOrOut,
     Fullscreen,
@@ -57,8 +57,8 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 model.is_menu_open = false;
             }
         }
-        Msg::SetIsLogged => {
-            model.is_logged = true;
+        Msg::SetIsLogged(is_logged) => {
+            model.is_logged = is_logged;
         }
         Msg::SetShareId(share_id) => {
             model.share_id = share_id;
@@ -0,0 +1,252 @@
+use seed::{self, prelude::*, *};
+use web_sys::{self, File, FileList};
+
+use crate::{
+    api::metadata::{self, Metadata},
+    models::picture::Gps,
+};
+
+// Pictures taken further away the same day start a new group
+const MAX_DISTANCE_KM: f64 = 30.;
+
+pub struct Cluster {
+    pub title: String,
+    pub files: Vec<File>,
+}
+
+// ------ ------
+//     Model
+// ------ -----
+pub struct Model {
+    by_location: bool,
+    is_reading: bool,
+}
+
+impl Model {
+    pub const fn new() -> Self {
+        Self {
+            by_location: false,
+            is_reading: false,
+        }
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+pub enum Msg {
+    ByLocationChanged,
+    FilesChanged(Option<FileList>),
+    Clustered(Vec<Cluster>),
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::ByLocationChanged => model.by_location = !model.by_location,
+        Msg::FilesChanged(files_opt) => {
+            if let Some(files) = files_opt {
+                model.is_reading = true;
+                let files: Vec<File> = (0..files.length()).filter_map(|i| files.get(i)).collect();
+                let by_location = model.by_location;
+                orders.perform_cmd(async move {
+                    let mut items = Vec::new();
+                    for file in files {
+                        let metadata = metadata::read(&file).await;
+                        items.push((file, metadata));
+                    }
+                    Msg::Clustered(cluster(items, by_location))
+                });
+            }
+        }
+        Msg::Clustered(_) => model.is_reading = false,
+    }
+}
+
+// Sorted by date taken, then split by day and optionally by distance
+fn cluster(mut items: Vec<(File, Metadata)>, by_location: bool) -> Vec<Cluster> {
+    items.sort_by_cached_key(|(_, metadata)| {
+        (metadata.taken_at.is_none(), metadata.taken_at.clone())
+    });
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut last_day: Option<String> = None;
+    let mut last_gps: Option<Gps> = None;
+    let mut part = 1;
+    for (file, metadata) in items {
+        let day = metadata
+            .taken_at
+            .as_ref()
+            .and_then(|taken_at| taken_at.get(..10))
+            .map(ToString::to_string);
+        let moved = by_location
+            && matches!((last_gps, metadata.gps), (Some(last), Some(gps)) if last.distance_km(&gps) > MAX_DISTANCE_KM);
+
+        if clusters.is_empty() || day != last_day {
+            part = 1;
+            clusters.push(Cluster {
+                title: day.clone().unwrap_or_else(|| "Undated".to_string()),
+                files: Vec::new(),
+            });
+        } else if moved {
+            part += 1;
+            clusters.push(Cluster {
+                title: format!("{} ({part})", day.clone().unwrap_or_default()),
+                files: Vec::new(),
+            });
+        }
+        if let Some(cluster) = clusters.last_mut() {
+            cluster.files.push(file);
+        }
+        last_day = day;
+        if metadata.gps.is_some() {
+            last_gps = metadata.gps;
+        }
+    }
+    clusters
+}
+
+// ------ ------
+//     View
+// ------ ------
+pub fn view(model: &Model, disabled: bool) -> Node<Msg> {
+    div![
+        C!["box", "mt-5"],
+        p![C!["title", "is-6", "has-text-link"], "Import trip"],
+        p![
+            C!["is-size-7", "mb-2"],
+            "One group is created for each day, titled with the date"
+        ],
+        div![
+            C!("field"),
+            input![
+                C!["switch", "is-small", "is-rounded"],
+                attrs! {
+                    At::Id => "import-by-location",
+                    At::Type => "checkbox",
+                    At::Checked => model.by_location.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::ByLocationChanged),
+            ],
+            label![
+                attrs! { At::For => "import-by-location" },
+                "Also split by location"
+            ],
+        ],
+        if model.is_reading {
+            progress![C!["progress", "is-small", "is-link"]]
+        } else {
+            div![
+                C!["file", "is-small", "is-link"],
+                label![
+                    C!("file-label"),
+                    input![
+                        C!("file-input"),
+                        attrs! {
+                            At::Type => "file",
+                            At::Name => "trip",
+                            At::Accept => "image/*",
+                            At::Multiple => "multiple",
+                            At::Disabled => disabled.as_at_value(),
+                        },
+                        ev(Ev::Change, |event| {
+                            let files = event
+                                .target()
+                                .and_then(|target| {
+                                    target.dyn_into::<web_sys::HtmlInputElement>().ok()
+                                })
+                                .and_then(|file_input| file_input.files());
+
+                            Msg::FilesChanged(files)
+                        })
+                    ],
+                    span![
+                        C!("file-cta"),
+                        span![C!("file-icon"), i![C!["ion-images"]]],
+                        span![C!("file-label"), "Select pictures"]
+                    ]
+                ]
+            ]
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use seed::prelude::*;
+    use wasm_bindgen_test::*;
+    use web_sys::File;
+
+    use super::{cluster, Cluster};
+    use crate::{api::metadata::Metadata, models::picture::Gps};
+
+    fn item(name: &str, taken_at: Option<&str>, gps: Option<(f64, f64)>) -> (File, Metadata) {
+        let file = File::new_with_str_sequence(&js_sys::Array::new(), name).unwrap();
+        let metadata = Metadata {
+            taken_at: taken_at.map(str::to_string),
+            gps: gps.map(|(lat, lon)| Gps { lat, lon }),
+            ..Metadata::default()
+        };
+        (file, metadata)
+    }
+
+    fn summary(clusters: &[Cluster]) -> Vec<(String, Vec<String>)> {
+        clusters
+            .iter()
+            .map(|cluster| {
+                let names = cluster.files.iter().map(File::name).collect();
+                (cluster.title.clone(), names)
+            })
+            .collect()
+    }
+
+    fn expected(clusters: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+        clusters
+            .iter()
+            .map(|(title, names)| {
+                let names = names.iter().map(ToString::to_string).collect();
+                ((*title).to_string(), names)
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn cluster_splits_by_day_and_keeps_the_undated_last() {
+        let items = vec![
+            item("c", Some("2023-07-15T09:00:00"), None),
+            item("d", None, None),
+            item("b", Some("2023-07-14T18:30:00"), None),
+            item("a", Some("2023-07-14T08:00:00"), None),
+        ];
+        assert_eq!(
+            summary(&cluster(items, false)),
+            expected(&[
+                ("2023-07-14", &["a", "b"]),
+                ("2023-07-15", &["c"]),
+                ("Undated", &["d"]),
+            ])
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn cluster_splits_by_location_when_asked() {
+        let paris = Some((48.86, 2.35));
+        let versailles = Some((48.80, 2.13));
+        let rouen = Some((49.44, 1.10));
+        let items = || {
+            vec![
+                item("a", Some("2023-07-14T08:00:00"), paris),
+                item("b", Some("2023-07-14T10:00:00"), versailles),
+                item("c", Some("2023-07-14T12:00:00"), None),
+                item("d", Some("2023-07-14T16:00:00"), rouen),
+            ]
+        };
+        assert_eq!(
+            summary(&cluster(items(), false)),
+            expected(&[("2023-07-14", &["a", "b", "c", "d"])])
+        );
+        assert_eq!(
+            summary(&cluster(items(), true)),
+            expected(&[("2023-07-14", &["a", "b", "c"]), ("2023-07-14 (2)", &["d"]),])
+        );
+    }
+}
@@ -1,9 +1,11 @@
-use crate::models::{
-    notif::{Notif, TypeNotifs},
-    page::TITLE_LOGIN,
-    vars::{AUTH_HEAD, BASE_URI},
+use crate::{
+    api::loginapi,
+    models::{
+        notif::{Notif, TypeNotifs},
+        page::TITLE_LOGIN,
+        session::Session,
+    },
 };
-use gloo_net::http::{Method, Request};
 use seed::{self, prelude::*, *};
 
 // ------ ------
@@ -22,31 +24,20 @@ pub enum Msg {
     Submit,
     UsernameChanged(String),
     PwdChanged(String),
-    SetAuth(String),
+    LoggedIn(Session),
     NotifyError,
 }
 
 pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
         Msg::Submit => {
-            orders.skip(); // No need to rerender
-            let uri = BASE_URI.to_string() + "login";
             let b64 = base64::encode(format!("{}:{}", model.username, model.password));
             let auth = format!("Basic {b64}");
+            model.password.clear();
 
-            orders.perform_cmd(async move {
-                let response = Request::new(&uri)
-                    .method(Method::POST)
-                    .header(AUTH_HEAD, &auth)
-                    .send()
-                    .await
-                    .expect("HTTP request failed");
-
-                if response.status() == 200 {
-                    Msg::SetAuth(auth.clone())
-                } else {
-                    Msg::NotifyError
-                }
+            orders.perform_cmd(async {
+                let session_res = loginapi::login(auth).await;
+                session_res.map_or(Msg::NotifyError, Msg::LoggedIn)
             });
         }
         Msg::NotifyError => {
@@ -57,7 +48,7 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         }
         Msg::UsernameChanged(username) => model.username = username,
         Msg::PwdChanged(password) => model.password = password,
-        Msg::SetAuth(_) => (),
+        Msg::LoggedIn(_) => (),
     }
 }
 
@@ -2,11 +2,13 @@ pub mod edit_album;
 pub mod error;
 pub mod group;
 pub mod header;
+pub mod import_trip;
 pub mod login;
 pub mod my_albums;
 pub mod my_sharings;
 pub mod notification;
 pub mod picture;
 pub mod slideshow;
+pub mod storage_cleanup;
 pub mod upload;
 pub mod view_album;
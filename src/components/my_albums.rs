@@ -1,171 +1,291 @@
 use gloo_console::*;
-use seed::{self, prelude::*, *};
+use seed::{
+    self,
+    browser::web_storage::{LocalStorage, WebStorage},
+    prelude::*,
+    *,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    api::albumapi,
+    api::{albumapi, backend::PictureSize, error::ApiError},
+    components::{notification::notify_api_error, storage_cleanup},
     models::{
         album::Album,
+        album_filter::{AlbumFilter, AlbumSort, ALBUM_SORT},
+        album_summary::AlbumSummary,
         notif::{Notif, TypeNotifs},
-        page::{LK_VIEW_ALBUM, TITLE_MY_ALBUMS},
+        page::{LK_EDIT_ALBUM, LK_VIEW_ALBUM, TITLE_MY_ALBUMS},
+        paged::Paged,
         state::{DeleteStatus, State},
     },
 };
 
+const LAYOUT_KEY: &str = "amaker_albums_layout";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Layout {
+    #[default]
+    List,
+    Grid,
+}
+
 // ------ ------
 //     Model
 // ------ -----
 #[derive(Default)]
 pub struct Model {
-    auth_header: String,
-    albums: Option<Vec<Album>>,
+    albums: Option<Vec<AlbumSummary>>,
+    next_cursor: Option<String>,
+    // Loading of a page or delay before the search, cancelled when dropped
+    fetch: Option<CmdHandle>,
+    layout: Layout,
+    // Id of the album being duplicated
+    duplicating: Option<String>,
+    // Id of the album being merged into another one
+    merging: Option<String>,
+    filter: AlbumFilter,
+    storage_cleanup: storage_cleanup::Model,
 }
 
+const SEARCH_DELAY: u32 = 300;
+
 // ------ ------
 //    Update
 // ------ ------
 pub enum Msg {
-    SetAuth(String),
     InitComp,
-    Received(Vec<Album>),
-    ErrorGet,
+    Search,
+    LoadMore,
+    Received(Paged<AlbumSummary>),
+    ErrorGet(ApiError),
     DeleteAllPics(String),
+    ReceivedToDelete(Album),
     DeleteAlbum(String),
     AskDelete(String),
     SuccessDelete(String),
-    ErrorDelete(String),
+    ErrorDelete(String, ApiError),
     CancelDelete(String),
     SuccessDeleteOnePic(String),
-    ErrorDeleteOnePic,
+    ErrorDeleteOnePic(ApiError),
+    Duplicate(String),
+    Duplicated(String),
+    ErrorDuplicate(ApiError),
+    Merge(String, String),
+    Merged,
+    ErrorMerge(ApiError),
+    StorageCleanup(storage_cleanup::Msg),
+    LayoutChanged(Layout),
+    QueryChanged(String),
+    SortChanged(Option<AlbumSort>),
+    SharedChanged(Option<bool>),
+    WithTripsChanged,
 }
 
 pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::SetAuth(auth_header) => model.auth_header = auth_header,
         Msg::InitComp => {
-            orders.skip(); // No need to rerender
-            let auth = model.auth_header.clone();
-            orders.perform_cmd(async {
-                let albums_opt = albumapi::get_my_ablums(auth).await;
-                albums_opt.map_or(Msg::ErrorGet, Msg::Received)
-            });
+            model.layout = LocalStorage::get(LAYOUT_KEY).unwrap_or_default();
+            model.filter = AlbumFilter::from_url(&Url::current());
+            orders.send_msg(Msg::Search);
         }
-        Msg::ErrorGet => {
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Error getting albums".to_string(),
-            });
+        Msg::Search => {
+            model.albums = None;
+            fetch_page(model, None, orders);
+        }
+        Msg::LoadMore => {
+            let cursor = model.next_cursor.clone();
+            fetch_page(model, cursor, orders);
+        }
+        Msg::LayoutChanged(layout) => {
+            model.layout = layout;
+            _ = LocalStorage::insert(LAYOUT_KEY, &layout);
+        }
+        Msg::QueryChanged(query) => {
+            model.filter.query = query;
+            update_url(&model.filter);
+            model.fetch =
+                Some(orders.perform_cmd_with_handle(cmds::timeout(SEARCH_DELAY, || Msg::Search)));
+        }
+        Msg::SortChanged(sort) => {
+            model.filter.sort = sort;
+            update_url(&model.filter);
+            orders.send_msg(Msg::Search);
         }
-        Msg::Received(albums) => {
-            model.albums = Some(albums);
+        Msg::SharedChanged(shared) => {
+            model.filter.shared = shared;
+            update_url(&model.filter);
+            orders.send_msg(Msg::Search);
+        }
+        Msg::WithTripsChanged => {
+            model.filter.with_trips = !model.filter.with_trips;
+            update_url(&model.filter);
+            orders.send_msg(Msg::Search);
+        }
+        Msg::ErrorGet(error) => {
+            model.fetch = None;
+            notify_api_error(orders, "Error getting albums", &error);
+        }
+        Msg::Received(page) => {
+            model.fetch = None;
+            model.albums.get_or_insert_with(Vec::new).extend(page.items);
+            model.next_cursor = page.next_cursor;
         }
         Msg::AskDelete(id) => {
-            if let Some(albums) = &mut model.albums {
-                if let Some(album) = albums.iter_mut().find(|a| a.id == id) {
-                    album.state = Some(State {
-                        delete_status: DeleteStatus::AskDelete,
-                        total: 0,
-                        current: 0,
-                    });
-                };
+            if let Some(album) = find_album(model, &id) {
+                album.state = Some(State {
+                    delete_status: DeleteStatus::AskDelete,
+                    total: 0,
+                    current: 0,
+                });
             }
         }
         Msg::CancelDelete(id) => {
-            if let Some(album) = model
-                .albums
-                .clone()
-                .unwrap_or_default()
-                .iter_mut()
-                .find(|a| a.id == id)
-            {
+            if let Some(album) = find_album(model, &id) {
                 album.state = None;
             }
         }
-        Msg::DeleteAllPics(album_id) => delete_all_pics(model, orders, album_id.as_str()),
-        Msg::ErrorDeleteOnePic => {
-            error!("Error deleting picture");
+        // The summary has no pictures, they are read from the full album
+        Msg::DeleteAllPics(album_id) => {
+            if let Some(album) = find_album(model, &album_id) {
+                album.state = Some(State {
+                    delete_status: DeleteStatus::Deleting,
+                    total: 0,
+                    current: 0,
+                });
+            }
+            orders.perform_cmd(async move {
+                match albumapi::get_album(Some(album_id.clone()), None).await {
+                    Ok(album) => Msg::ReceivedToDelete(album),
+                    Err(error) => Msg::ErrorDelete(album_id, error),
+                }
+            });
+        }
+        Msg::ReceivedToDelete(album) => delete_all_pics(model, orders, &album),
+        Msg::ErrorDeleteOnePic(err) => {
+            error!(format!("Error deleting picture: {err}"));
+        }
+        Msg::Duplicate(id) => {
+            model.duplicating = Some(id.clone());
+            orders.perform_cmd(async {
+                let id_res = albumapi::duplicate_album(id).await;
+                id_res.map_or_else(Msg::ErrorDuplicate, Msg::Duplicated)
+            });
+        }
+        Msg::Duplicated(id) => {
+            model.duplicating = None;
+            let url = Url::new().add_path_part(LK_EDIT_ALBUM).add_path_part(id);
+            orders.notify(subs::UrlRequested::new(url));
+        }
+        Msg::ErrorDuplicate(error) => {
+            model.duplicating = None;
+            notify_api_error(orders, "Error duplicating album", &error);
+        }
+        Msg::Merge(source_id, target_id) => {
+            let title = |id: &str| {
+                model
+                    .albums
+                    .iter()
+                    .flatten()
+                    .find(|a| a.id == id)
+                    .map(|a| a.title.clone())
+            };
+            let question = format!(
+                "Move the groups of \"{}\" to \"{}\" and delete it?",
+                title(&source_id).unwrap_or_default(),
+                title(&target_id).unwrap_or_default()
+            );
+            if window().confirm_with_message(&question).unwrap_or(false) {
+                model.merging = Some(source_id.clone());
+                orders.perform_cmd(async {
+                    let res = albumapi::merge_albums(source_id, target_id).await;
+                    res.map_or_else(Msg::ErrorMerge, |()| Msg::Merged)
+                });
+            }
+        }
+        Msg::Merged => {
+            model.merging = None;
+            orders.notify(Notif {
+                notif_type: TypeNotifs::Success,
+                message: "Albums merged".to_string(),
+            });
+            orders.send_msg(Msg::Search);
+        }
+        Msg::ErrorMerge(error) => {
+            model.merging = None;
+            notify_api_error(orders, "Error merging albums", &error);
+        }
+        Msg::StorageCleanup(msg) => {
+            storage_cleanup::update(
+                msg,
+                &mut model.storage_cleanup,
+                &mut orders.proxy(Msg::StorageCleanup),
+            );
         }
         Msg::SuccessDeleteOnePic(id) => {
-            if let Some(albums) = &mut model.albums.clone() {
-                if let Some(album) = albums.iter_mut().find(|a| a.id == id) {
-                    if let Some(state) = &mut album.state {
-                        state.current += 1;
-                    }
-                }
+            if let Some(state) = find_album(model, &id).and_then(|album| album.state.as_mut()) {
+                state.current += 1;
             }
         }
         Msg::DeleteAlbum(id) => {
-            let auth = model.auth_header.clone();
             let id_del = id.clone();
             orders.perform_cmd(async {
-                let success = albumapi::delete_ablum(id_del, auth).await;
-                if success {
-                    Msg::SuccessDelete(id)
-                } else {
-                    Msg::ErrorDelete(id)
+                match albumapi::delete_ablum(id_del).await {
+                    Ok(()) => Msg::SuccessDelete(id),
+                    Err(error) => Msg::ErrorDelete(id, error),
                 }
             });
         }
-        Msg::ErrorDelete(id) => {
-            if let Some(album) = model
-                .albums
-                .clone()
-                .unwrap_or_default()
-                .iter_mut()
-                .find(|a| a.id == id)
-            {
+        Msg::ErrorDelete(id, error) => {
+            if let Some(album) = find_album(model, &id) {
                 album.state = None;
             }
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Error deleting album".to_string(),
-            });
+            notify_api_error(orders, "Error deleting album", &error);
         }
         Msg::SuccessDelete(id) => {
             if let Some(albums) = &mut model.albums {
-                let index = albums.iter().position(|album| *album.id == id).unwrap();
-                albums.remove(index);
+                albums.retain(|album| album.id != id);
             }
         }
     }
 }
 
-fn delete_all_pics(model: &mut Model, orders: &mut impl Orders<Msg>, album_id: &str) {
-    if let Some(album) = model
-        .albums
-        .clone()
-        .unwrap_or_default()
-        .iter_mut()
-        .find(|a| a.id == album_id)
-    {
-        if let Some(state) = &mut album.state {
-            state.delete_status = DeleteStatus::Deleting;
+// Only the last requested page is received
+fn fetch_page(model: &mut Model, cursor: Option<String>, orders: &mut impl Orders<Msg>) {
+    let filter = model.filter.clone();
+    model.fetch = Some(orders.perform_cmd_with_handle(async move {
+        let page_res = albumapi::get_album_summaries(filter, cursor).await;
+        page_res.map_or_else(Msg::ErrorGet, Msg::Received)
+    }));
+}
 
-            //Delete all pictures
-            if let Some(groups) = album.groups.clone() {
-                let grp_pic_ids = groups.iter().map(|g| {
-                    g.pictures.clone().map_or_else(Vec::new, |pictures| {
-                        pictures.iter().map(|p| p.public_id.clone()).collect()
-                    })
-                });
-                let pic_ids: Vec<String> = grp_pic_ids.into_iter().flatten().collect();
-                state.total = pic_ids.len();
+fn find_album<'a>(model: &'a mut Model, id: &str) -> Option<&'a mut AlbumSummary> {
+    model.albums.iter_mut().flatten().find(|a| a.id == id)
+}
 
-                for pic_id in pic_ids {
-                    let id_success = album_id.to_string();
-                    orders.perform_cmd(async move {
-                        let res = albumapi::delete_picture(pic_id).await;
-                        if res {
-                            Msg::SuccessDeleteOnePic(id_success)
-                        } else {
-                            Msg::ErrorDeleteOnePic
-                        }
-                    });
-                }
-            }
-        }
+// Replaced to survive reloads without adding history entries
+fn update_url(filter: &AlbumFilter) {
+    Url::current()
+        .set_search(filter.to_search())
+        .go_and_replace();
+}
 
-        orders.send_msg(Msg::DeleteAlbum(album_id.to_string()));
+fn delete_all_pics(model: &mut Model, orders: &mut impl Orders<Msg>, album: &Album) {
+    let pic_ids = album.public_ids();
+    if let Some(state) = find_album(model, &album.id).and_then(|summary| summary.state.as_mut()) {
+        state.total = pic_ids.len();
+    }
+    for pic_id in pic_ids {
+        let id_success = album.id.clone();
+        orders.perform_cmd(async move {
+            let res = albumapi::delete_picture(pic_id).await;
+            res.map_or_else(Msg::ErrorDeleteOnePic, |()| {
+                Msg::SuccessDeleteOnePic(id_success)
+            })
+        });
     }
+
+    orders.send_msg(Msg::DeleteAlbum(album.id.clone()));
 }
 
 // ------ ------
@@ -173,91 +293,351 @@ fn delete_all_pics(model: &mut Model, orders: &mut impl Orders<Msg>, album_id: &
 // ------ ------
 pub fn view(model: &Model) -> Node<Msg> {
     div![
-        C!["column", "is-centered", "is-half"],
+        C![
+            "column",
+            "is-centered",
+            if model.layout == Layout::Grid {
+                "is-10"
+            } else {
+                "is-half"
+            }
+        ],
         div![
             C!("box"),
-            p![C!["title", "is-5", "has-text-link"], TITLE_MY_ALBUMS],
-            if model.albums.is_some() {
-                div![model.albums.as_ref().unwrap().iter().map(|album| {
-                    let id_del = album.id.clone();
-					let id_can = album.id.clone();
-                    p![
-                        C!("panel-block"),
-                        div![
-                            C![
-                                "container",
-                                "is-flex", 
-                                "is-justify-content-space-between"
-                            ],
-                            div![
-								if (album.state).is_some() {
-									let state = album.state.as_ref().unwrap();
-									match state.delete_status {
-										DeleteStatus::AskDelete => {
-											span!["Delete this album ?"]
-										},
-										DeleteStatus::Deleting => {
-											progress![
-												C!["progress", "is-danger"],
-												attrs! { At::Value => state.current, At::Max => state.total }
-											]
-										}
-									}
-								}
-								else {
-									a![
-										attrs! {
-											At::Title => "Open",
-											At::Href => format!("/{LK_VIEW_ALBUM}/{id_del}"),
-										},
-										&album.title
-									]
-								}
-							],
-                            div![
-                                C!["is-align-content-flex-end"],
-                                if (album.state).is_some() {
-									if album.state.as_ref().unwrap().delete_status == DeleteStatus::AskDelete {
-										div![
-											button![
-												C!["button", "is-link", "is-light", "is-small", "mr-2"],
-												span![C!("icon"), i![C!("ion-close-circled")]],
-												span!["NO"],
-												ev(Ev::Click, |_| Msg::CancelDelete(id_del)),
-											],
-											button![
-												C!["button", "is-danger", "is-light", "is-small"],
-												span![C!("icon"), i![C!("ion-close-circled")]],
-												span!["YES"],
-												ev(Ev::Click, |_| Msg::DeleteAllPics(id_can)),
-											]
-										]
-									} else {
-										empty!()
-									}
-                                } else {
-                                    button![
-                                        C!["button", "is-link", "is-light", "is-small"],
-                                        span![C!("icon"), i![C!("ion-close-circled")]],
-                                        span!["Delete"],
-                                        ev(Ev::Click, |_| Msg::AskDelete(id_del)),
-                                    ]
-                                }
+            div![
+                C![
+                    "is-flex",
+                    "is-justify-content-space-between",
+                    "is-align-items-center",
+                    "mb-4"
+                ],
+                p![
+                    C!["title", "is-5", "has-text-link", "mb-0"],
+                    TITLE_MY_ALBUMS
+                ],
+                view_layout(model.layout),
+            ],
+            view_filter(&model.filter),
+            model.albums.as_ref().map_or_else(view_progress, |albums| {
+                if albums.is_empty() {
+                    p![C!["is-size-7", "has-text-grey"], "No album found"]
+                } else {
+                    div![
+                        match model.layout {
+                            Layout::List => view_list(model, albums),
+                            Layout::Grid => view_grid(model, albums),
+                        },
+                        IF!(model.next_cursor.is_some() => view_load_more(model.fetch.is_some())),
+                    ]
+                }
+            }),
+        ],
+        storage_cleanup::view(&model.storage_cleanup).map_msg(Msg::StorageCleanup),
+    ]
+}
+
+fn view_layout(layout: Layout) -> Node<Msg> {
+    div![
+        C!["buttons", "has-addons", "are-small"],
+        [
+            (Layout::List, "ion-navicon-round", "List"),
+            (Layout::Grid, "ion-grid", "Grid")
+        ]
+        .iter()
+        .map(|&(value, icon, title)| {
+            button![
+                C!["button", IF!(layout == value => "is-link is-selected")],
+                attrs! { At::Title => title },
+                span![C!("icon"), i![C!(icon)]],
+                ev(Ev::Click, move |_| Msg::LayoutChanged(value)),
+            ]
+        }),
+    ]
+}
+
+fn view_filter(filter: &AlbumFilter) -> Node<Msg> {
+    div![
+        C!["mb-4"],
+        div![
+            C!["field"],
+            p![
+                C!["control", "has-icons-left"],
+                input![
+                    C!["input", "is-small"],
+                    attrs! {
+                        At::Type => "search",
+                        At::Placeholder => "Search titles, groups, captions and trips",
+                        At::Value => filter.query,
+                    },
+                    input_ev(Ev::Input, Msg::QueryChanged),
+                ],
+                span![C!["icon", "is-small", "is-left"], i![C!("ion-search")]],
+            ]
+        ],
+        div![
+            C!["is-flex", "is-flex-wrap-wrap", "is-align-items-center"],
+            div![
+                C!["select", "is-small", "mr-2", "mb-2"],
+                select![
+                    option![
+                        attrs! { At::Value => "", At::Selected => filter.sort.is_none().as_at_value() },
+                        "Sort by"
+                    ],
+                    ALBUM_SORT.iter().map(|&sort| {
+                        option![
+                            attrs! { At::Value => sort.key(), At::Selected => (filter.sort == Some(sort)).as_at_value() },
+                            sort.to_string()
+                        ]
+                    }),
+                    input_ev(Ev::Change, |value| {
+                        Msg::SortChanged(ALBUM_SORT.iter().copied().find(|sort| sort.key() == value))
+                    }),
+                ]
+            ],
+            div![
+                C!["select", "is-small", "mr-2", "mb-2"],
+                select![
+                    [(None, "All albums"), (Some(true), "Shared"), (Some(false), "Not shared")]
+                        .iter()
+                        .map(|&(shared, label)| {
+                            option![
+                                attrs! {
+                                    At::Value => label,
+                                    At::Selected => (filter.shared == shared).as_at_value(),
+                                },
+                                label
                             ]
+                        }),
+                    input_ev(Ev::Change, |value| Msg::SharedChanged(match value.as_str() {
+                        "Shared" => Some(true),
+                        "Not shared" => Some(false),
+                        _ => None,
+                    })),
+                ]
+            ],
+            label![
+                C!["checkbox", "is-size-7", "mb-2"],
+                input![
+                    C!("mr-1"),
+                    attrs! {
+                        At::Type => "checkbox",
+                        At::Checked => filter.with_trips.as_at_value(),
+                    },
+                    ev(Ev::Change, |_| Msg::WithTripsChanged),
+                ],
+                "With trips"
+            ],
+        ]
+    ]
+}
+
+fn view_load_more(is_loading: bool) -> Node<Msg> {
+    div![
+        C!["has-text-centered", "mt-4"],
+        button![
+            C![
+                "button",
+                "is-link",
+                "is-light",
+                "is-small",
+                IF!(is_loading => "is-loading")
+            ],
+            "Load more albums",
+            ev(Ev::Click, |_| Msg::LoadMore),
+        ]
+    ]
+}
+
+fn view_list(model: &Model, albums: &[AlbumSummary]) -> Node<Msg> {
+    div![albums.iter().map(|album| {
+        let id_del = album.id.clone();
+        p![
+            C!("panel-block"),
+            div![
+                C![
+                    "container",
+                    "is-flex",
+                    "is-justify-content-space-between"
+                ],
+                div![album.state.as_ref().map_or_else(
+                    || a![
+                        C!["is-flex", "is-align-items-center"],
+                        attrs! {
+                            At::Title => "Open",
+                            At::Href => format!("/{LK_VIEW_ALBUM}/{id_del}"),
+                        },
+                        album.cover.as_ref().map_or(empty!(), |cover| figure![
+                            C!["image", "is-48x48", "mr-2"],
+                            img![attrs! { At::Src => albumapi::picture_uri(cover, PictureSize::Thumb) }],
+                        ]),
+                        &album.title
+                    ],
+                    view_delete_state,
+                )],
+                div![
+                    C!["is-flex", "is-align-content-flex-end"],
+                    IF!(album.state.is_none() => view_duplicate(model, album)),
+                    IF!(album.state.is_none() => view_merge(model, album, albums)),
+                    view_delete(album)
+                ]
+            ]
+        ]
+    })]
+}
+
+fn view_grid(model: &Model, albums: &[AlbumSummary]) -> Node<Msg> {
+    div![
+        C!["columns", "is-multiline"],
+        albums.iter().map(|album| {
+            let href = format!("/{LK_VIEW_ALBUM}/{}", album.id);
+            div![
+                C!["column", "is-one-third-tablet", "is-one-quarter-desktop"],
+                div![
+                    C!["card", "album-card"],
+                    a![
+                        C!("card-image"),
+                        attrs! { At::Title => "Open", At::Href => &href },
+                        figure![
+                            C!["image", "is-4by3", "album-card-cover"],
+                            album.cover.as_ref().map_or_else(
+                                || span![C!["icon", "is-large", "has-text-grey-light"], i![C!("ion-images")]],
+                                |cover| img![attrs! { At::Src => albumapi::picture_uri(cover, PictureSize::Thumb) }],
+                            ),
                         ]
-                    ]
-                })]
-            } else {
-                div![(0..4).map(|_| {
-                    p![
-                        C!("panel-block"),
-                        progress![
-                            C!["progress", "is-small", "table-progress"],
-                            attrs! { At::Max => 100 }
+                    ],
+                    div![
+                        C!["card-content", "p-3"],
+                        a![
+                            C!["title", "is-6", "has-text-link"],
+                            attrs! { At::Href => href },
+                            &album.title
                         ],
+                        p![
+                            C!["is-size-7", "mt-2"],
+                            format!("{} groups, {} pictures", album.groups_count, album.pictures_count)
+                        ],
+                        album.updated_on().map_or(empty!(), |updated_on| p![
+                            C!["is-size-7"],
+                            format!("Modified {updated_on}")
+                        ]),
+                        p![
+                            C!["is-size-7"],
+                            span![C!["icon", "is-small"], i![C!("ion-android-share-alt")]],
+                            format!("{} shares", album.shares_count)
+                        ],
+                        album.state.as_ref().map_or(empty!(), view_delete_state),
+                        IF!(album.state.is_none() => div![C!("mt-2"), view_merge(model, album, albums)]),
+                    ],
+                    footer![
+                        C!("card-footer"),
+                        IF!(album.state.is_none() => div![C!["card-footer-item"], view_duplicate(model, album)]),
+                        div![C!["card-footer-item"], view_delete(album)]
                     ]
-                })]
-            }
+                ]
+            ]
+        })
+    ]
+}
+
+// A single duplication or merge at a time, the pictures are copied one by one
+fn view_duplicate(model: &Model, album: &AlbumSummary) -> Node<Msg> {
+    let id_dup = album.id.clone();
+    button![
+        C![
+            "button",
+            "is-link",
+            "is-light",
+            "is-small",
+            "mr-2",
+            IF!(model.duplicating.as_ref() == Some(&album.id) => "is-loading")
+        ],
+        attrs! { At::Disabled => is_busy(model).as_at_value() },
+        span![C!("icon"), i![C!("ion-ios-copy")]],
+        span!["Duplicate"],
+        ev(Ev::Click, |_| Msg::Duplicate(id_dup)),
+    ]
+}
+
+// Into one of the loaded albums
+fn view_merge(model: &Model, album: &AlbumSummary, albums: &[AlbumSummary]) -> Node<Msg> {
+    let source_id = album.id.clone();
+    div![
+        C![
+            "select",
+            "is-small",
+            "mr-2",
+            IF!(model.merging.as_ref() == Some(&album.id) => "is-loading")
+        ],
+        select![
+            attrs! {
+                At::Title => "Move its groups to another album and delete it",
+                At::Disabled => is_busy(model).as_at_value(),
+            },
+            option![
+                attrs! { At::Value => "", At::Selected => true.as_at_value() },
+                "Merge into"
+            ],
+            albums
+                .iter()
+                .filter(|target| target.id != album.id)
+                .map(|target| { option![attrs! { At::Value => target.id }, &target.title] }),
+            input_ev(Ev::Change, move |target_id| {
+                (!target_id.is_empty()).then(|| Msg::Merge(source_id, target_id))
+            }),
         ]
     ]
 }
+
+const fn is_busy(model: &Model) -> bool {
+    model.duplicating.is_some() || model.merging.is_some()
+}
+
+fn view_delete_state(state: &State) -> Node<Msg> {
+    match state.delete_status {
+        DeleteStatus::AskDelete => span!["Delete this album ?"],
+        DeleteStatus::Deleting | DeleteStatus::Moving => progress![
+            C!["progress", "is-danger"],
+            attrs! { At::Value => state.current, At::Max => state.total }
+        ],
+    }
+}
+
+fn view_delete(album: &AlbumSummary) -> Node<Msg> {
+    let id_del = album.id.clone();
+    let id_can = album.id.clone();
+    match &album.state {
+        Some(state) if state.delete_status == DeleteStatus::AskDelete => div![
+            button![
+                C!["button", "is-link", "is-light", "is-small", "mr-2"],
+                span![C!("icon"), i![C!("ion-close-circled")]],
+                span!["NO"],
+                ev(Ev::Click, |_| Msg::CancelDelete(id_del)),
+            ],
+            button![
+                C!["button", "is-danger", "is-light", "is-small"],
+                span![C!("icon"), i![C!("ion-close-circled")]],
+                span!["YES"],
+                ev(Ev::Click, |_| Msg::DeleteAllPics(id_can)),
+            ]
+        ],
+        Some(_) => empty!(),
+        None => button![
+            C!["button", "is-link", "is-light", "is-small"],
+            span![C!("icon"), i![C!("ion-close-circled")]],
+            span!["Delete"],
+            ev(Ev::Click, |_| Msg::AskDelete(id_del)),
+        ],
+    }
+}
+
+fn view_progress() -> Node<Msg> {
+    div![(0..4).map(|_| {
+        p![
+            C!("panel-block"),
+            progress![
+                C!["progress", "is-small", "table-progress"],
+                attrs! { At::Max => 100 }
+            ],
+        ]
+    })]
+}
@@ -1,10 +1,7 @@
 use crate::{
-    api::sharingapi,
-    models::{
-        notif::{Notif, TypeNotifs},
-        page::TITLE_MY_SHARINGS,
-        sharing::Sharing,
-    },
+    api::{error::ApiError, sharingapi},
+    components::notification::notify_api_error,
+    models::{page::TITLE_MY_SHARINGS, paged::Paged, sharing::Sharing},
 };
 use seed::{self, prelude::*, *};
 
@@ -13,53 +10,51 @@ use seed::{self, prelude::*, *};
 // ------ -----
 #[derive(Default)]
 pub struct Model {
-    auth_header: String,
     sharings: Option<Vec<Sharing>>,
+    next_cursor: Option<String>,
+    is_loading: bool,
 }
 
 // ------ ------
 //    Update
 // ------ ------
 pub enum Msg {
-    SetAuth(String),
     InitComp,
-    Received(Vec<Sharing>),
-    ErrorGet,
+    LoadMore,
+    Received(Paged<Sharing>),
+    ErrorGet(ApiError),
     Delete(String),
     SuccessDelete(String),
-    ErrorDelete,
+    ErrorDelete(ApiError),
 }
 
 pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::SetAuth(auth_header) => model.auth_header = auth_header,
         Msg::InitComp => {
-            orders.skip(); // No need to rerender
-            let auth = model.auth_header.clone();
-            orders.perform_cmd(async {
-                let sharings_opt = sharingapi::get_my_sharings(auth).await;
-                sharings_opt.map_or(Msg::ErrorGet, Msg::Received)
-            });
+            model.sharings = None;
+            get_page(model, None, orders);
         }
-        Msg::ErrorGet => {
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Error getting sharings".to_string(),
-            });
+        Msg::LoadMore => {
+            let cursor = model.next_cursor.clone();
+            get_page(model, cursor, orders);
         }
-        Msg::Received(sharings) => {
-            model.sharings = Some(sharings);
+        Msg::ErrorGet(error) => {
+            model.is_loading = false;
+            notify_api_error(orders, "Error getting sharings", &error);
+        }
+        Msg::Received(page) => {
+            model.is_loading = false;
+            model
+                .sharings
+                .get_or_insert_with(Vec::new)
+                .extend(page.items);
+            model.next_cursor = page.next_cursor;
         }
         Msg::Delete(id) => {
-            let auth = model.auth_header.clone();
             let id_del = id.clone();
             orders.perform_cmd(async {
-                let success = sharingapi::delete_sharing(id_del, auth).await;
-                if success {
-                    Msg::SuccessDelete(id)
-                } else {
-                    Msg::ErrorDelete
-                }
+                let res = sharingapi::delete_sharing(id_del).await;
+                res.map_or_else(Msg::ErrorDelete, |()| Msg::SuccessDelete(id))
             });
         }
         Msg::SuccessDelete(id) => {
@@ -68,15 +63,20 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 sharings.remove(index);
             }
         }
-        Msg::ErrorDelete => {
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Error deleting sharing".to_string(),
-            });
+        Msg::ErrorDelete(error) => {
+            notify_api_error(orders, "Error deleting sharing", &error);
         }
     }
 }
 
+fn get_page(model: &mut Model, cursor: Option<String>, orders: &mut impl Orders<Msg>) {
+    model.is_loading = true;
+    orders.perform_cmd(async {
+        let sharings_res = sharingapi::get_my_sharings(cursor).await;
+        sharings_res.map_or_else(Msg::ErrorGet, Msg::Received)
+    });
+}
+
 // ------ ------
 //     View
 // ------ ------
@@ -87,69 +87,84 @@ pub fn view(model: &Model) -> Node<Msg> {
             C!("box"),
             p![C!["title", "is-5", "has-text-link"], TITLE_MY_SHARINGS],
             if model.sharings.is_some() {
-                div![model.sharings.as_ref().unwrap().iter().map(|sharing| {
-                    let base_url = web_sys::window().unwrap().location().origin().unwrap();
-                    let id_del = sharing.id.clone();
-                    let id_del_mob = sharing.id.clone();
-                    div![
-                        p![
-                            C!("panel-block"),
-                            div![
-                                C![
-                                    "container",
-                                    "is-flex",
-                                    "is-justify-content-space-between",
-                                    "is-align-items-center"
-                                ],
-                                div![&sharing.album_name],
+                div![
+                    model.sharings.as_ref().unwrap().iter().map(|sharing| {
+                        let base_url = web_sys::window().unwrap().location().origin().unwrap();
+                        let id_del = sharing.id.clone();
+                        let id_del_mob = sharing.id.clone();
+                        div![
+                            p![
+                                C!("panel-block"),
                                 div![
-                                    C!("is-flex"),
+                                    C![
+                                        "container",
+                                        "is-flex",
+                                        "is-justify-content-space-between",
+                                        "is-align-items-center"
+                                    ],
+                                    div![&sharing.album_name],
                                     div![
-                                        C!["tag", "is-link", "is-light", "ml-2"],
-                                        attrs! {At::Title => "Number of views"},
-                                        span![C!("icon"), i![C!("ion-eye")]],
-                                        &sharing.nb_view
+                                        C!("is-flex"),
+                                        div![
+                                            C!["tag", "is-link", "is-light", "ml-2"],
+                                            attrs! {At::Title => "Number of views"},
+                                            span![C!("icon"), i![C!("ion-eye")]],
+                                            &sharing.nb_view
+                                        ],
+                                        div![
+                                            C!["tag", "is-danger", "is-light", "ml-2"],
+                                            attrs! {At::Title => "Number of likes"},
+                                            span![C!("icon"), i![C!("ion-heart")]],
+                                            &sharing.nb_like
+                                        ],
                                     ],
                                     div![
-                                        C!["tag", "is-danger", "is-light", "ml-2"],
-                                        attrs! {At::Title => "Number of likes"},
-                                        span![C!("icon"), i![C!("ion-heart")]],
-                                        &sharing.nb_like
+                                        C![
+                                            "has-text-grey",
+                                            "is-size-7",
+                                            "ml-2",
+                                            "is-hidden-mobile"
+                                        ],
+                                        format!("{base_url}/share/{}", &sharing.id)
                                     ],
-                                ],
+                                    div![
+                                        C!["is-align-content-flex-end", "is-hidden-mobile"],
+                                        button![
+                                            C!["button", "is-link", "is-light", "is-small", "ml-2"],
+                                            span![C!("icon"), i![C!("ion-close-circled")]],
+                                            span!["Delete"],
+                                            ev(Ev::Click, |_| Msg::Delete(id_del)),
+                                        ]
+                                    ]
+                                ]
+                            ],
+                            p![
+                                C!["panel-block", "is-hidden-desktop", "is-hidden-tablet"],
                                 div![
-                                    C!["has-text-grey", "is-size-7", "ml-2", "is-hidden-mobile"],
+                                    C!["has-text-grey", "is-size-7"],
                                     format!("{base_url}/share/{}", &sharing.id)
                                 ],
                                 div![
-                                    C!["is-align-content-flex-end", "is-hidden-mobile"],
+                                    C!["is-align-content-flex-end"],
                                     button![
                                         C!["button", "is-link", "is-light", "is-small", "ml-2"],
                                         span![C!("icon"), i![C!("ion-close-circled")]],
                                         span!["Delete"],
-                                        ev(Ev::Click, |_| Msg::Delete(id_del)),
+                                        ev(Ev::Click, |_| Msg::Delete(id_del_mob)),
                                     ]
                                 ]
-                            ]
-                        ],
-                        p![
-                            C!["panel-block", "is-hidden-desktop", "is-hidden-tablet"],
-                            div![
-                                C!["has-text-grey", "is-size-7"],
-                                format!("{base_url}/share/{}", &sharing.id)
                             ],
-                            div![
-                                C!["is-align-content-flex-end"],
-                                button![
-                                    C!["button", "is-link", "is-light", "is-small", "ml-2"],
-                                    span![C!("icon"), i![C!("ion-close-circled")]],
-                                    span!["Delete"],
-                                    ev(Ev::Click, |_| Msg::Delete(id_del_mob)),
-                                ]
-                            ]
-                        ],
-                    ]
-                })]
+                        ]
+                    }),
+                    IF!(model.next_cursor.is_some() => div![
+                        C!["has-text-centered", "mt-4"],
+                        button![
+                            C!["button", "is-link", "is-light", "is-small", IF!(model.is_loading => "is-loading")],
+                            "Load more sharings",
+                            ev(Ev::Click, |_| Msg::LoadMore),
+                        ]
+                    ])
+                ]
             } else {
                 div![(0..4).map(|_| {
                     p![
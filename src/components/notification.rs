@@ -1,4 +1,10 @@
-use crate::models::notif::{Notif, TypeNotifs};
+use crate::{
+    api::error::ApiError,
+    models::{
+        notif::{Notif, TypeNotifs},
+        session::SessionExpired,
+    },
+};
 use seed::{prelude::*, *};
 
 // ------ ------
@@ -55,3 +61,18 @@ pub fn view(model: &Model) -> Node<Msg> {
         &model.notif.message
     ]
 }
+
+pub fn notify_api_error<Ms: 'static>(
+    orders: &mut impl Orders<Ms>,
+    message: &str,
+    error: &ApiError,
+) {
+    if *error == ApiError::Unauthorized {
+        orders.notify(SessionExpired);
+    } else {
+        orders.notify(Notif {
+            notif_type: TypeNotifs::Error,
+            message: format!("{message}: {error}"),
+        });
+    }
+}
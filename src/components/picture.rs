@@ -2,12 +2,8 @@ use seed::{self, prelude::*, *};
 use uuid::Uuid;
 
 use crate::{
-    api::albumapi,
-    models::{
-        notif::{Notif, TypeNotifs},
-        picture::Picture,
-        vars::THUMB_URI,
-    },
+    api::{albumapi, backend::PictureSize},
+    models::picture::Picture,
 };
 
 // ------ ------
@@ -15,40 +11,49 @@ use crate::{
 // ------ ------
 pub enum Msg {
     UpdateCaption(Uuid, String, String),
-    DeletePicture(Uuid, String, String),
-    DeletePictureSuccess(Uuid, String),
-    DeleteFail,
+    // Shift-click selects the range from the last selected picture
+    Select(Uuid, String, bool),
+    AskDelete(Uuid, String),
+    CancelDelete,
+    ConfirmDelete(Uuid),
+    SetCover(String),
 }
 
-pub fn update(msg: Msg, orders: &mut impl Orders<Msg>) {
-    match msg {
-        Msg::DeletePicture(group_id, public_id, asset_id) => {
-            orders.skip(); // No need to rerender
-            orders.perform_cmd(async move {
-                let success = albumapi::delete_picture(public_id).await;
-                if success {
-                    Msg::DeletePictureSuccess(group_id, asset_id)
-                } else {
-                    Msg::DeleteFail
-                }
-            });
-        }
-        Msg::DeletePictureSuccess(_, _) | Msg::UpdateCaption(_, _, _) => (),
-        Msg::DeleteFail => {
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Error deleting picture".to_string(),
-            });
-        }
-    }
-}
-
-pub fn view(group_id: Uuid, picture: &Picture) -> Node<Msg> {
+pub fn view(
+    group_id: Uuid,
+    picture: &Picture,
+    is_selected: bool,
+    is_asking_delete: bool,
+    is_cover: bool,
+) -> Node<Msg> {
     let asset_id = picture.asset_id.clone();
     let asset_id2 = picture.asset_id.clone();
-    let public_id = picture.clone().public_id;
+    let asset_id3 = picture.asset_id.clone();
+    let asset_id4 = picture.asset_id.clone();
     div![
-        C!["container", "columns", "is-vcentered", "is-mobile", "mt-3"],
+        C![
+            "container",
+            "columns",
+            "is-vcentered",
+            "is-mobile",
+            "mt-3",
+            IF!(is_selected => "is-selected")
+        ],
+        div![
+            C!["column", "is-flex-grow-0"],
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Title => "Select (Shift to select a range)",
+                    At::Checked => is_selected.as_at_value(),
+                },
+                mouse_ev(Ev::Click, move |event| Msg::Select(
+                    group_id,
+                    asset_id3,
+                    event.shift_key()
+                )),
+            ],
+        ],
         div![
             attrs! { At::Draggable => true },
             C!["column", "is-flex-grow-0"],
@@ -58,9 +63,7 @@ pub fn view(group_id: Uuid, picture: &Picture) -> Node<Msg> {
             C!["column", "is-flex-grow-0"],
             figure![
                 C!["image", "is-128x128"],
-                img![
-                    attrs! { At::Src => format!("{THUMB_URI}{}.{}", picture.public_id, picture.format) }
-                ]
+                img![attrs! { At::Src => albumapi::picture_uri(picture, PictureSize::Thumb) }]
             ]
         ],
         div![
@@ -86,14 +89,42 @@ pub fn view(group_id: Uuid, picture: &Picture) -> Node<Msg> {
             ],
             div![
                 C!("control"),
-                button![
-                    C!["button", "is-link", "is-light", "is-small"],
-                    span![C!("icon"), i![C!("ion-close-circled")]],
-                    span!["Delete"],
-                    ev(Ev::Click, move |_| Msg::DeletePicture(
-                        group_id, public_id, asset_id2
-                    ))
-                ]
+                if is_asking_delete {
+                    div![
+                        span![C!["mr-2"], "Delete this picture ?"],
+                        button![
+                            C!["button", "is-link", "is-light", "is-small", "mr-2"],
+                            span![C!("icon"), i![C!("ion-close-circled")]],
+                            span!["NO"],
+                            ev(Ev::Click, |_| Msg::CancelDelete),
+                        ],
+                        button![
+                            C!["button", "is-danger", "is-light", "is-small"],
+                            span![C!("icon"), i![C!("ion-close-circled")]],
+                            span!["YES"],
+                            ev(Ev::Click, move |_| Msg::ConfirmDelete(group_id)),
+                        ]
+                    ]
+                } else {
+                    div![
+                        button![
+                            C!["button", "is-link", "is-light", "is-small", "mr-2"],
+                            span![C!("icon"), i![C!("ion-close-circled")]],
+                            span!["Delete"],
+                            ev(Ev::Click, move |_| Msg::AskDelete(group_id, asset_id2))
+                        ],
+                        if is_cover {
+                            span![C!["tag", "is-primary", "is-light"], "Cover"]
+                        } else {
+                            button![
+                                C!["button", "is-link", "is-light", "is-small"],
+                                span![C!("icon"), i![C!("ion-image")]],
+                                span!["Set as cover"],
+                                ev(Ev::Click, move |_| Msg::SetCover(asset_id4))
+                            ]
+                        }
+                    ]
+                }
             ]
         ]
     ]
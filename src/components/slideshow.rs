@@ -1,10 +1,10 @@
 use crate::{
-    api::albumapi,
+    api::{albumapi, backend::PictureSize, error::ApiError},
+    components::notification::notify_api_error,
     models::{
         album::Album,
         picture::Picture,
         trip::{TranspMode, Trip},
-        vars::{IMG_URI, VERY_LOW_URI},
     },
 };
 use seed::{self, prelude::*, *};
@@ -37,7 +37,6 @@ impl std::fmt::Display for Element {
 //     Model
 // ------ -----
 pub struct Model {
-    auth_header: String,
     album: Album,
     slides: Vec<Slide>,
     slide: Slide,
@@ -50,7 +49,6 @@ pub struct Model {
 impl Model {
     pub fn new() -> Self {
         Self {
-            auth_header: String::new(),
             album: Album::new(),
             slides: Vec::new(),
             slide: Slide {
@@ -71,10 +69,9 @@ impl Model {
 //    Update
 // ------ ------
 pub enum Msg {
-    SetAuth(String),
     InitComp(Option<String>, Option<String>),
     InitSlides,
-    ErrorGet,
+    ErrorGet(ApiError),
     Received(Album),
     Next,
     ShowCaption,
@@ -85,7 +82,6 @@ pub enum Msg {
 
 pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::SetAuth(auth_header) => model.auth_header = auth_header,
         Msg::InitComp(id, share_id) => {
             orders.skip(); // No need to rerender
             model.error = false;
@@ -97,15 +93,16 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 picture: None,
                 trip: None,
             };
-            let auth = model.auth_header.clone();
             orders.perform_cmd(async {
-                let opt_album = albumapi::get_album(id, share_id, auth).await;
-                opt_album.map_or(Msg::ErrorGet, Msg::Received)
+                let album_res = albumapi::get_album(id, share_id).await;
+                album_res.map_or_else(Msg::ErrorGet, Msg::Received)
             });
         }
-        Msg::ErrorGet => {
+        Msg::ErrorGet(error) => {
             model.error = true;
+            notify_api_error(orders, "Error getting album", &error);
         }
+
         Msg::Received(album) => {
             model.album = album;
             orders.send_msg(Msg::InitSlides);
@@ -152,7 +149,7 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         }
         Msg::PreLoadPic => {
             if let Some(pic) = &model.slide.picture {
-                let uri = format!("{IMG_URI}{}.{}", pic.public_id, pic.format);
+                let uri = albumapi::picture_uri(pic, PictureSize::Full);
                 orders.perform_cmd(async {
                     let _ok = albumapi::preload_picture(uri).await;
                     Msg::ShowPic
@@ -170,12 +167,8 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
 
 fn init_slides(model: &mut Model) {
     // Cover
-    let grps = model.album.groups.clone().unwrap_or_default();
-    for grp in &grps {
-        if let Some(pic) = grp.pictures.clone().unwrap_or_default().first() {
-            model.cover = format!("url({VERY_LOW_URI}{}.{})", pic.public_id, pic.format);
-            break;
-        }
+    if let Some(pic) = model.album.cover_picture() {
+        model.cover = format!("url({})", albumapi::picture_uri(pic, PictureSize::VeryLow));
     }
 
     // Slide for album title
@@ -274,7 +267,7 @@ pub fn view(model: &Model) -> Node<Msg> {
                     IF!(*show_pic =>
                         img![
                             C!["slideshow-image"],
-                            attrs! { At::Src => format!("{IMG_URI}{}.{}", picture.public_id, picture.format) }
+                            attrs! { At::Src => albumapi::picture_uri(picture, PictureSize::Full) }
                         ]
                     ),
                     IF!(*show_pic =>
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use seed::{self, prelude::*, *};
+
+use crate::{
+    api::{albumapi, backend::PictureSize, error::ApiError},
+    components::notification::notify_api_error,
+    models::{
+        draft::Draft,
+        picture::Picture,
+        state::{DeleteStatus, State},
+    },
+};
+
+// Recent uploads may belong to an album being edited elsewhere
+const GRACE_PERIOD_MS: f64 = 24. * 60. * 60. * 1000.;
+
+// ------ ------
+//     Model
+// ------ -----
+#[derive(Default)]
+pub struct Model {
+    is_loading: bool,
+    // Pictures stored on Cloudinary and referenced by no album
+    orphans: Option<Vec<Picture>>,
+    state: Option<State>,
+}
+
+// ------ ------
+//    Update
+// ------ ------
+pub enum Msg {
+    Search,
+    Received(Vec<Picture>),
+    ErrorGet(ApiError),
+    AskDelete,
+    CancelDelete,
+    DeleteAll,
+    SuccessDeleteOne(String),
+    ErrorDeleteOne(ApiError),
+}
+
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::Search => {
+            model.is_loading = true;
+            model.state = None;
+            orders.perform_cmd(async move {
+                let orphans_res = get_orphans().await;
+                orphans_res.map_or_else(Msg::ErrorGet, Msg::Received)
+            });
+        }
+        Msg::Received(orphans) => {
+            model.is_loading = false;
+            model.orphans = Some(orphans);
+        }
+        Msg::ErrorGet(error) => {
+            model.is_loading = false;
+            notify_api_error(orders, "Error getting pictures", &error);
+        }
+        Msg::AskDelete => {
+            model.state = Some(State {
+                delete_status: DeleteStatus::AskDelete,
+                total: 0,
+                current: 0,
+            });
+        }
+        Msg::CancelDelete => model.state = None,
+        Msg::DeleteAll => {
+            let public_ids: Vec<String> = model
+                .orphans
+                .iter()
+                .flatten()
+                .map(|p| p.public_id.clone())
+                .collect();
+            model.state = Some(State {
+                delete_status: DeleteStatus::Deleting,
+                total: public_ids.len(),
+                current: 0,
+            });
+            for public_id in public_ids {
+                orders.perform_cmd(async move {
+                    let res = albumapi::delete_picture(public_id.clone()).await;
+                    res.map_or_else(Msg::ErrorDeleteOne, |()| Msg::SuccessDeleteOne(public_id))
+                });
+            }
+        }
+        Msg::SuccessDeleteOne(public_id) => {
+            if let Some(orphans) = &mut model.orphans {
+                orphans.retain(|p| p.public_id != public_id);
+            }
+            delete_done(model);
+        }
+        // The picture stays in the list to be deleted again
+        Msg::ErrorDeleteOne(error) => {
+            delete_done(model);
+            notify_api_error(orders, "Error deleting picture", &error);
+        }
+    }
+}
+
+fn delete_done(model: &mut Model) {
+    if let Some(state) = &mut model.state {
+        state.current += 1;
+        if state.current >= state.total {
+            model.state = None;
+        }
+    }
+}
+
+// The drafts reference the pictures of unsaved changes
+async fn get_orphans() -> Result<Vec<Picture>, ApiError> {
+    let pictures = albumapi::list_pictures().await?;
+    let mut used: HashSet<String> = albumapi::get_used_public_ids().await?.into_iter().collect();
+    used.extend(
+        Draft::all()
+            .iter()
+            .flat_map(|draft| draft.album.public_ids()),
+    );
+    let now = js_sys::Date::now();
+    Ok(pictures
+        .into_iter()
+        .filter(|p| !used.contains(&p.public_id) && !is_recent(p, now))
+        .collect())
+}
+
+// A picture without date is not recent
+fn is_recent(picture: &Picture, now: f64) -> bool {
+    picture.created_at.as_ref().map_or(false, |created_at| {
+        now - js_sys::Date::parse(created_at) < GRACE_PERIOD_MS
+    })
+}
+
+// ------ ------
+//     View
+// ------ ------
+pub fn view(model: &Model) -> Node<Msg> {
+    div![
+        C!["box", "mt-5"],
+        p![C!["title", "is-6", "has-text-link"], "Storage cleanup"],
+        p![
+            C!["is-size-7", "mb-2"],
+            "Pictures uploaded more than a day ago and kept by no album or draft"
+        ],
+        if model.is_loading {
+            progress![C!["progress", "is-small", "is-link"]]
+        } else {
+            model
+                .orphans
+                .as_ref()
+                .map_or_else(view_search_button, |orphans| view_orphans(model, orphans))
+        }
+    ]
+}
+
+fn view_search_button() -> Node<Msg> {
+    button![
+        C!["button", "is-link", "is-light", "is-small"],
+        span![C!("icon"), i![C!("ion-search")]],
+        span!["Find unused pictures"],
+        ev(Ev::Click, |_| Msg::Search),
+    ]
+}
+
+fn view_orphans(model: &Model, orphans: &[Picture]) -> Node<Msg> {
+    if orphans.is_empty() {
+        return div![
+            p![C!["is-size-7", "mb-2"], "No unused picture"],
+            view_search_button(),
+        ];
+    }
+    div![
+        div![
+            C!["is-flex", "is-flex-wrap-wrap", "mb-2"],
+            orphans.iter().map(|picture| {
+                figure![
+                    C!["image", "is-64x64", "m-1"],
+                    attrs! { At::Title => picture.public_id },
+                    img![attrs! { At::Src => albumapi::picture_uri(picture, PictureSize::Thumb) }],
+                ]
+            })
+        ],
+        match &model.state {
+            Some(state) if state.delete_status == DeleteStatus::Deleting => progress![
+                C!["progress", "is-danger"],
+                attrs! { At::Value => state.current, At::Max => state.total }
+            ],
+            Some(_) => div![
+                span![
+                    C!["mr-2"],
+                    format!("Delete these {} pictures ?", orphans.len())
+                ],
+                button![
+                    C!["button", "is-link", "is-light", "is-small", "mr-2"],
+                    span!["NO"],
+                    ev(Ev::Click, |_| Msg::CancelDelete),
+                ],
+                button![
+                    C!["button", "is-danger", "is-light", "is-small"],
+                    span!["YES"],
+                    ev(Ev::Click, |_| Msg::DeleteAll),
+                ],
+            ],
+            None => button![
+                C!["button", "is-danger", "is-light", "is-small"],
+                span![C!("icon"), i![C!("ion-trash-a")]],
+                span![format!("Delete {} unused pictures", orphans.len())],
+                ev(Ev::Click, |_| Msg::AskDelete),
+            ],
+        }
+    ]
+}
@@ -1,75 +1,193 @@
-use std::path::Path;
+use std::{collections::VecDeque, path::Path};
 
-use load_dotenv::load_dotenv;
 use seed::{self, prelude::*, *};
 use uuid::Uuid;
-use web_sys::{self, FileList, FormData};
+use web_sys::{self, AbortController, File, FileList, FormData};
 
 use crate::{
-    api::albumapi,
+    api::{albumapi, error::ApiError, imaging, metadata},
+    components::notification::notify_api_error,
     models::{
-        notif::{Notif, TypeNotifs},
+        config,
         picture::Picture,
+        upload::{Upload, UploadStatus},
     },
 };
 
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: u32 = 1000;
+const MAX_CONCURRENT: usize = 3;
+
+// ------ ------
+//     Model
+// ------ ------
+// The uploads wait in the queue until one of the running ones ends
+pub struct Model {
+    queue: VecDeque<(Upload, String, Uuid)>,
+    running: usize,
+}
+
+impl Model {
+    pub const fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            running: 0,
+        }
+    }
+    // The running uploads end by themselves
+    pub fn clear(&mut self) {
+        for (upload, _, _) in self.queue.drain(..) {
+            upload.abort.abort();
+        }
+    }
+}
+
 // ------ ------
 //    Update
 // ------ ------
 pub enum Msg {
     FilesChanged(Option<FileList>, String, Uuid),
-    RenderFakePictures(u32, Uuid),
-    SendUpload(FormData, String, Uuid),
-    Success(Picture, Uuid),
-    Error,
+    AddUploads(Vec<Upload>, Uuid),
+    SendUpload(Upload, String, Uuid),
+    Progress(Uuid, Uuid, UploadStatus),
+    Success(Picture, Uuid, Uuid),
+    Error(ApiError, Upload, String, Uuid),
+    Retry(Upload, String, Uuid),
+    // Replaces the upload of the group, with a new controller
+    Renewed(Upload, Uuid),
+    Cancel(Upload, Uuid),
 }
 
-pub fn update(msg: Msg, orders: &mut impl Orders<Msg>) {
+pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
         Msg::FilesChanged(files_opt, album_id, group_id) => {
-            load_dotenv!();
             if let Some(files) = files_opt {
-                let count = files.length();
-                orders.send_msg(Msg::RenderFakePictures(count, group_id));
-                for i in 0..count {
-                    if let Some(file) = files.get(i) {
-                        if let Ok(form_data) = FormData::new() {
-                            let upload_preset = env!("CLD_UPLOAD_PRESET");
-                            let folder = format!("amaker/{album_id}");
-                            let file_res = form_data.append_with_blob("file", &file);
-                            let preset_res_ =
-                                form_data.append_with_str("upload_preset", upload_preset);
-                            let folder_res = form_data.append_with_str("folder", folder.as_str());
-                            if file_res.is_ok() && preset_res_.is_ok() && folder_res.is_ok() {
-                                orders.send_msg(Msg::SendUpload(form_data, file.name(), group_id));
-                            }
-                        }
-                    }
-                }
+                let files = (0..files.length()).filter_map(|i| files.get(i)).collect();
+                start(files, &album_id, group_id, orders);
             }
         }
-        Msg::SendUpload(form_data, name, group_id) => {
+        Msg::SendUpload(upload, album_id, group_id) => {
             orders.skip(); // No need to rerender
-            orders.perform_cmd(async move {
-                let pic_opt = albumapi::upload_picture(form_data).await;
-                match pic_opt {
-                    Some(mut pic) => {
-                        let name = Path::new(&name).file_stem().unwrap_or_default();
-                        let name = name.to_str().unwrap_or_default().to_string();
-                        pic.caption = Some(name);
-                        Msg::Success(pic, group_id)
-                    }
-                    None => Msg::Error,
-                }
-            });
+            model.queue.push_back((upload, album_id, group_id));
+            start_next(model, orders);
+        }
+        Msg::Success(_, _, _) => {
+            model.running = model.running.saturating_sub(1);
+            start_next(model, orders);
+        }
+        Msg::Error(error, mut upload, album_id, group_id) => {
+            model.running = model.running.saturating_sub(1);
+            start_next(model, orders);
+            if error == ApiError::Cancelled {
+                return;
+            }
+            upload.attempts += 1;
+            if upload.attempts < MAX_ATTEMPTS {
+                // Retry with an exponential backoff
+                let delay = RETRY_DELAY * 2_u32.pow(upload.attempts - 1);
+                orders.send_msg(Msg::Progress(group_id, upload.id, UploadStatus::Queued));
+                orders.perform_cmd(cmds::timeout(delay, move || {
+                    Msg::SendUpload(upload, album_id, group_id)
+                }));
+            } else {
+                orders.send_msg(Msg::Progress(group_id, upload.id, UploadStatus::Failed));
+                notify_api_error(orders, "Error uploading picture", &error);
+            }
+        }
+        Msg::Retry(mut upload, album_id, group_id) => {
+            // The controller of a cancelled upload stays aborted
+            let Ok(abort) = AbortController::new() else {
+                return;
+            };
+            upload.abort = abort;
+            upload.attempts = 0;
+            upload.status = UploadStatus::Queued;
+            orders.send_msg(Msg::Renewed(upload.clone(), group_id));
+            orders.send_msg(Msg::SendUpload(upload, album_id, group_id));
+        }
+        Msg::Cancel(upload, _) => {
+            upload.abort.abort();
+        }
+        Msg::AddUploads(_, _) | Msg::Progress(_, _, _) | Msg::Renewed(_, _) => (),
+    }
+}
+
+/// Adds the files to the group and queues their upload.
+pub fn start(files: Vec<File>, album_id: &str, group_id: Uuid, orders: &mut impl Orders<Msg>) {
+    let uploads: Vec<Upload> = files.into_iter().filter_map(Upload::new).collect();
+    orders.send_msg(Msg::AddUploads(uploads.clone(), group_id));
+    for upload in uploads {
+        orders.send_msg(Msg::SendUpload(upload, album_id.to_string(), group_id));
+    }
+}
+
+// The cancelled uploads are dropped from the queue
+fn start_next(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    while model.running < MAX_CONCURRENT {
+        let Some((upload, album_id, group_id)) = model.queue.pop_front() else {
+            return;
+        };
+        if !upload.abort.signal().aborted() {
+            model.running += 1;
+            send_upload(upload, album_id, group_id, orders);
         }
-        Msg::RenderFakePictures(_, _) | Msg::Success(_, _) => (),
-        Msg::Error => {
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Error uploading picture".to_string(),
-            });
+    }
+}
+
+fn send_upload(upload: Upload, album_id: String, group_id: Uuid, orders: &mut impl Orders<Msg>) {
+    let signal = upload.abort.signal();
+    let upload_id = upload.id;
+    orders.send_msg(Msg::Progress(group_id, upload_id, UploadStatus::Processing));
+
+    let sender = orders.msg_sender();
+    orders.perform_cmd(async move {
+        // The EXIF is read first since the resizing drops it
+        let metadata = metadata::read(&upload.file).await;
+        let file = imaging::prepare(&upload.file).await;
+        if signal.aborted() {
+            return Msg::Error(ApiError::Cancelled, upload, album_id, group_id);
         }
+        let Some(form_data) = build_form_data(&file, &album_id) else {
+            return Msg::Error(ApiError::Serialization, upload, album_id, group_id);
+        };
+        sender(Some(Msg::Progress(
+            group_id,
+            upload_id,
+            UploadStatus::Uploading(0),
+        )));
+        let on_progress = Box::new(move |percent| {
+            sender(Some(Msg::Progress(
+                group_id,
+                upload_id,
+                UploadStatus::Uploading(percent),
+            )));
+        });
+        let pic_res = albumapi::upload_picture(form_data, on_progress, signal).await;
+        match pic_res {
+            Ok(mut pic) => {
+                let name = upload.file.name();
+                let name = Path::new(&name).file_stem().unwrap_or_default();
+                let name = name.to_str().unwrap_or_default().to_string();
+                pic.caption = Some(name);
+                metadata.apply(&mut pic);
+                Msg::Success(pic, group_id, upload_id)
+            }
+            Err(error) => Msg::Error(error, upload, album_id, group_id),
+        }
+    });
+}
+
+fn build_form_data(file: &File, album_id: &str) -> Option<FormData> {
+    let form_data = FormData::new().ok()?;
+    let upload_preset = config::get().upload_preset;
+    let folder = format!("amaker/{album_id}");
+    let file_res = form_data.append_with_blob("file", file);
+    let preset_res = form_data.append_with_str("upload_preset", &upload_preset);
+    let folder_res = form_data.append_with_str("folder", folder.as_str());
+    if file_res.is_ok() && preset_res.is_ok() && folder_res.is_ok() {
+        Some(form_data)
+    } else {
+        None
     }
 }
 
@@ -111,3 +229,43 @@ pub fn view(album_id: String, group_id: Uuid) -> Node<Msg> {
         ]
     ]
 }
+
+pub fn view_uploads(album_id: &str, group_id: Uuid, uploads: &[Upload]) -> Node<Msg> {
+    div![uploads.iter().map(|upload| {
+        let upload_retry = upload.clone();
+        let upload_cancel = upload.clone();
+        let album_id = album_id.to_string();
+        div![
+            C!["is-flex", "is-align-items-center", "mt-2"],
+            span![C!["is-size-7", "upload-name"], upload.file.name()],
+            match upload.status {
+                UploadStatus::Queued => span![C!["tag", "is-light", "ml-2"], "Queued"],
+                UploadStatus::Processing =>
+                    span![C!["tag", "is-info", "is-light", "ml-2"], "Resizing"],
+                UploadStatus::Uploading(percent) => progress![
+                    C!["progress", "is-small", "is-link", "upload-progress", "ml-2"],
+                    attrs! { At::Value => percent, At::Max => 100 },
+                    format!("{percent}%")
+                ],
+                UploadStatus::Failed => span![C!["tag", "is-danger", "is-light", "ml-2"], "Failed"],
+                UploadStatus::Done => span![C!["tag", "is-success", "is-light", "ml-2"], "Done"],
+            },
+            IF!(upload.status == UploadStatus::Failed =>
+                button![
+                    C!["button", "is-link", "is-light", "is-small", "ml-2"],
+                    span![C!("icon"), i![C!("ion-refresh")]],
+                    span!["Retry"],
+                    ev(Ev::Click, move |_| Msg::Retry(upload_retry, album_id, group_id)),
+                ]
+            ),
+            IF!(upload.is_cancellable() =>
+                button![
+                    C!["button", "is-light", "is-small", "ml-2"],
+                    span![C!("icon"), i![C!("ion-close-circled")]],
+                    span!["Cancel"],
+                    ev(Ev::Click, move |_| Msg::Cancel(upload_cancel, group_id)),
+                ]
+            ),
+        ]
+    })]
+}
@@ -1,14 +1,15 @@
 use crate::{
-    api::{albumapi, sharingapi},
+    api::{albumapi, backend::PictureSize, error::ApiError, export, sharingapi},
+    components::notification::notify_api_error,
     models::{
         album::Album,
         notif::{Notif, TypeNotifs},
         page::{LK_EDIT_ALBUM, LK_SHARESLIDE, LK_SLIDESHOW, TITLE_EDIT_ALBUM, TITLE_SLIDESHOW},
         picture::Picture,
         sharing::{AddViewLike, Sharing},
-        vars::{IMG_URI, THUMB_URI},
     },
 };
+use gloo_console::error;
 use seed::{self, prelude::*, *};
 
 use super::error;
@@ -17,25 +18,28 @@ use super::error;
 //     Model
 // ------ -----
 pub struct Model {
-    auth_header: String,
     album: Album,
     is_loaded: bool,
     share_id: Option<String>,
     error: bool,
     is_liked: bool,
     picture: Option<Picture>,
+    is_duplicating: bool,
+    // Percentage of pictures downloaded
+    export_progress: Option<u32>,
 }
 
 impl Model {
     pub const fn new() -> Self {
         Self {
-            auth_header: String::new(),
             album: Album::new(),
             is_loaded: false,
             share_id: None,
             error: false,
             is_liked: false,
             picture: None,
+            is_duplicating: false,
+            export_progress: None,
         }
     }
 }
@@ -44,13 +48,19 @@ impl Model {
 //    Update
 // ------ ------
 pub enum Msg {
-    SetAuth(String),
     InitComp(Option<String>, Option<String>),
-    ErrorGet,
+    ErrorGet(ApiError),
     Received(Album),
     Share,
     ShareSuccess(String),
-    ShareError,
+    ShareError(ApiError),
+    Duplicate,
+    Duplicated(String),
+    DuplicateError(ApiError),
+    Export,
+    ExportProgress(u32),
+    Exported(Vec<u8>),
+    ExportError(ApiError),
     AddViewLike(bool, bool),
     ShowPicture(Picture),
     HidePicture,
@@ -58,22 +68,21 @@ pub enum Msg {
 
 pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
-        Msg::SetAuth(auth_header) => model.auth_header = auth_header,
         Msg::InitComp(id, share_id) => {
             orders.skip(); // No need to rerender
             model.error = false;
             model.is_liked = false;
             model.picture = None;
-            let auth = model.auth_header.clone();
             model.share_id = share_id.clone();
             orders.perform_cmd(async {
-                let opt_album = albumapi::get_album(id, share_id, auth).await;
-                opt_album.map_or(Msg::ErrorGet, Msg::Received)
+                let album_res = albumapi::get_album(id, share_id).await;
+                album_res.map_or_else(Msg::ErrorGet, Msg::Received)
             });
             orders.send_msg(Msg::AddViewLike(true, false));
         }
-        Msg::ErrorGet => {
+        Msg::ErrorGet(error) => {
             model.error = true;
+            notify_api_error(orders, "Error getting album", &error);
         }
         Msg::Received(album) => {
             model.is_loaded = true;
@@ -81,7 +90,6 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         }
         Msg::Share => {
             orders.skip(); // No need to rerender
-            let auth = model.auth_header.clone();
             let album = model.album.clone();
             orders.perform_cmd(async move {
                 let sharing = Sharing {
@@ -91,15 +99,12 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                     nb_like: 0,
                     nb_view: 0,
                 };
-                let opt_id = sharingapi::add_sharing(auth, sharing).await;
-                opt_id.map_or(Msg::ShareError, Msg::ShareSuccess)
+                let id_res = sharingapi::add_sharing(sharing).await;
+                id_res.map_or_else(Msg::ShareError, Msg::ShareSuccess)
             });
         }
-        Msg::ShareError => {
-            orders.notify(Notif {
-                notif_type: TypeNotifs::Error,
-                message: "Error sharing".to_string(),
-            });
+        Msg::ShareError(error) => {
+            notify_api_error(orders, "Error sharing", &error);
         }
         Msg::ShareSuccess(id) => {
             let base_url = web_sys::window().unwrap().location().origin().unwrap();
@@ -108,19 +113,72 @@ pub fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 message: format!("Share your album with this URL : {base_url}/share/{id}"),
             });
         }
+        Msg::Duplicate => {
+            model.is_duplicating = true;
+            let id = model.album.id.clone();
+            orders.perform_cmd(async {
+                let id_res = albumapi::duplicate_album(id).await;
+                id_res.map_or_else(Msg::DuplicateError, Msg::Duplicated)
+            });
+        }
+        Msg::Duplicated(id) => {
+            model.is_duplicating = false;
+            let url = Url::new().add_path_part(LK_EDIT_ALBUM).add_path_part(id);
+            orders.notify(subs::UrlRequested::new(url));
+        }
+        Msg::DuplicateError(error) => {
+            model.is_duplicating = false;
+            notify_api_error(orders, "Error duplicating album", &error);
+        }
+        Msg::Export => {
+            model.export_progress = Some(0);
+            let album = model.album.clone();
+            let sender = orders.msg_sender();
+            orders.perform_cmd(async move {
+                let on_progress = Box::new(move |percent| {
+                    sender(Some(Msg::ExportProgress(percent)));
+                });
+                let zip_res = export::export_album(&album, on_progress).await;
+                zip_res.map_or_else(Msg::ExportError, Msg::Exported)
+            });
+        }
+        Msg::ExportProgress(percent) => model.export_progress = Some(percent),
+        Msg::Exported(zip) => {
+            model.export_progress = None;
+            let file_name: String = model
+                .album
+                .title
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            if let Err(err) = export::save_file(&zip, &format!("{file_name}.zip")) {
+                error!(format!("Error saving export: {err:?}"));
+            }
+        }
+        Msg::ExportError(error) => {
+            model.export_progress = None;
+            notify_api_error(orders, "Error exporting album", &error);
+        }
         Msg::AddViewLike(is_view, is_like) => {
             if is_like {
                 model.is_liked = true;
             }
             if let Some(share_id) = model.share_id.clone() {
-                let auth = model.auth_header.clone();
                 orders.perform_cmd(async move {
                     let add_view_like = AddViewLike {
                         view: is_view,
                         like: is_like,
                         share_id,
                     };
-                    sharingapi::add_view_like(auth, add_view_like).await;
+                    if let Err(err) = sharingapi::add_view_like(add_view_like).await {
+                        error!(format!("Error adding view or like: {err}"));
+                    }
                 });
             }
         }
@@ -152,6 +210,10 @@ pub fn view(model: &Model) -> Node<Msg> {
             C!["column", "is-two-thirds"],
                 div![
                     C!["column"],
+                    IF!(model.share_id.is_some() => model.album.cover_picture().map_or(empty!(), |cover| figure![
+                        C!["image", "album-view-cover", "mb-4"],
+                        img![attrs! { At::Src => albumapi::picture_uri(cover, PictureSize::Full) }],
+                    ])),
                     div![C!["title", "is-5", "has-text-link"], &model.album.title],
                     div![
                         C!["is-flex", "mb-2"],
@@ -168,6 +230,25 @@ pub fn view(model: &Model) -> Node<Msg> {
                                     span![C!("icon"), i![C!("ion-android-share-alt")]],
                                     span!["Share"],
                                     ev(Ev::Click, |_| Msg::Share),
+                                ],
+                                button![
+                                    C!["button", "is-link", "is-light", "is-small", "mr-2", IF!(model.is_duplicating => "is-loading")],
+                                    span![C!("icon"), i![C!("ion-ios-copy")]],
+                                    span!["Duplicate"],
+                                    ev(Ev::Click, |_| Msg::Duplicate),
+                                ],
+                                button![
+                                    C!["button", "is-link", "is-light", "is-small", "mr-2"],
+                                    attrs! {
+                                        At::Title => "Download a ZIP to view offline",
+                                        At::Disabled => model.export_progress.is_some().as_at_value(),
+                                    },
+                                    span![C!("icon"), i![C!("ion-archive")]],
+                                    span![model.export_progress.map_or_else(
+                                        || "Export".to_string(),
+                                        |percent| format!("Export {percent}%")
+                                    )],
+                                    ev(Ev::Click, |_| Msg::Export),
                                 ]
                             ]
                         ),
@@ -204,7 +285,8 @@ pub fn view(model: &Model) -> Node<Msg> {
                                             C!["mr-1", "album-view-pic"],
                                             figure![
                                                 C!["image", "is-128x128", "m-1"],
-                                                img![attrs!{ At::Src => format!("{THUMB_URI}{}.{}", picture.public_id, picture.format) }],
+                                                attrs!{ At::Title => picture.details() },
+                                                img![attrs!{ At::Src => albumapi::picture_uri(picture, PictureSize::Thumb) }],
                                                 ev(Ev::Click, |_| Msg::ShowPicture(pic)),
                                             ],
                                             span![picture.caption.clone()],
@@ -215,7 +297,7 @@ pub fn view(model: &Model) -> Node<Msg> {
                     })])
         ], |picture| img![
             C!["album-view-fullpic"],
-                attrs! { At::Src => format!("{IMG_URI}{}.{}", picture.public_id, picture.format) },
+                attrs! { At::Src => albumapi::picture_uri(picture, PictureSize::Full) },
                 ev(Ev::Click, |_| Msg::HidePicture),
         ])
     } else {
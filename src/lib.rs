@@ -5,9 +5,12 @@
 #![allow(clippy::unused_unit)]
 
 use crate::components::*;
+use api::{configapi, error::ApiError};
 use models::{
-    notif::Notif,
+    config::{self, Config},
+    notif::{Notif, TypeNotifs},
     page::{Page, LK_LOGIN},
+    session::{Session, SessionExpired},
 };
 use seed::{prelude::*, *};
 
@@ -15,20 +18,29 @@ mod api;
 mod components;
 mod models;
 
+// Run with `cargo make test_h_firefox`, the local backend needs the localStorage of a browser
+#[cfg(test)]
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
 // ------ ------
 //     Init
 // ------ ------
-fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
+fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
     orders.subscribe(Msg::ShowNotif);
     orders.subscribe(Msg::UrlChanged);
+    orders.subscribe(|_: SessionExpired| Msg::SessionExpired);
 
-    orders.notify(subs::UrlRequested::new(url));
+    orders.perform_cmd(async {
+        let config_res = configapi::get_config().await;
+        config_res.map_or_else(Msg::ConfigError, Msg::ConfigLoaded)
+    });
 
     let login_page = models::page::Page::Login;
 
     Model {
-        is_logged: false,
+        is_config_loaded: false,
         header: header::Model::new(login_page.clone()),
+        edit_url: None,
         notification: notification::Model::new(),
         my_albums: my_albums::Model::default(),
         edit_album: edit_album::Model::new(),
@@ -44,8 +56,9 @@ fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
 //     Model
 // ------ ------
 struct Model {
-    is_logged: bool,
+    is_config_loaded: bool,
     header: header::Model,
+    edit_url: Option<Url>,
     page: models::page::Page,
     my_albums: my_albums::Model,
     edit_album: edit_album::Model,
@@ -60,6 +73,8 @@ struct Model {
 //    Update
 // ------ ------
 enum Msg {
+    ConfigLoaded(Config),
+    ConfigError(ApiError),
     Header(header::Msg),
     MyAlbums(my_albums::Msg),
     EditAlbum(edit_album::Msg),
@@ -68,20 +83,33 @@ enum Msg {
     Login(login::Msg),
     UrlChanged(subs::UrlChanged),
     InitComp(Option<String>),
-    SetAuth(String),
+    StartSession(Session),
+    SessionExpired,
     Notification(notification::Msg),
-    SetIsLogged,
     ShowNotif(Notif),
     MySharings(my_sharings::Msg),
 }
 
 fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
+        Msg::ConfigLoaded(config) => {
+            config::set(config);
+            model.is_config_loaded = true;
+            if let Some(session) = Session::load() {
+                orders.send_msg(Msg::StartSession(session));
+            }
+            orders.notify(subs::UrlRequested::new(Url::current()));
+        }
+        Msg::ConfigError(error) => {
+            orders.notify(Notif {
+                notif_type: TypeNotifs::Error,
+                message: format!("Error loading configuration: {error}"),
+            });
+        }
         Msg::Header(msg) => {
             if matches!(msg, header::Msg::LogInOrOut) {
-                if model.is_logged {
-                    orders.send_msg(Msg::SetAuth(String::new()));
-                    model.is_logged = false;
+                if Session::is_started() {
+                    end_session(orders);
                 }
                 let url = Url::new().add_path_part(LK_LOGIN);
                 orders.notify(subs::UrlRequested::new(url));
@@ -131,6 +159,29 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 _ => models::page::Page::MyAlbums,
             };
 
+            if is_editing(&model.page) {
+                // Any other url, even of another album, leaves the editor
+                let is_same_album = model
+                    .edit_url
+                    .as_ref()
+                    .map_or(false, |edit_url| edit_url.path() == url.path());
+                if is_same_album {
+                    orders.skip();
+                    return;
+                }
+                if model.edit_album.has_unsaved_changes() && !confirm_leave() {
+                    // Stay on the editor and put its url back
+                    if let Some(edit_url) = &model.edit_url {
+                        edit_url.go_and_replace();
+                    }
+                    orders.skip();
+                    return;
+                }
+                orders.send_msg(Msg::EditAlbum(edit_album::Msg::Exit));
+            }
+            if is_editing(&page) {
+                model.edit_url = Some(Url::current());
+            }
             model.page = page.clone();
 
             orders.send_msg(Msg::Header(header::Msg::SetPage(page)));
@@ -139,28 +190,32 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
             orders.send_msg(Msg::InitComp(opt_id));
         }
         Msg::InitComp(opt_id) => {
-            if model.is_logged || model.page == Page::Share || model.page == Page::ShareSlide {
+            if Session::is_started() || model.page == Page::Share || model.page == Page::ShareSlide
+            {
                 init_comp(&model.page, opt_id, orders);
             }
         }
         Msg::Login(msg) => {
-            if let login::Msg::SetAuth(ref auth) = msg {
-                orders.send_msg(Msg::SetIsLogged);
-                orders.send_msg(Msg::SetAuth(auth.clone()));
+            if let login::Msg::LoggedIn(ref session) = msg {
+                orders.send_msg(Msg::StartSession(session.clone()));
                 orders.notify(subs::UrlRequested::new(Url::new()));
             }
             login::update(msg, &mut model.login, &mut orders.proxy(Msg::Login));
         }
-        Msg::SetIsLogged => {
-            model.is_logged = true;
-            orders.send_msg(Msg::Header(header::Msg::SetIsLogged));
+        Msg::StartSession(session) => {
+            session.start();
+            orders.send_msg(Msg::Header(header::Msg::SetIsLogged(true)));
         }
-        Msg::SetAuth(auth) => {
-            orders.send_msg(Msg::EditAlbum(edit_album::Msg::SetAuth(auth.clone())));
-            orders.send_msg(Msg::MyAlbums(my_albums::Msg::SetAuth(auth.clone())));
-            orders.send_msg(Msg::ViewAlbum(view_album::Msg::SetAuth(auth.clone())));
-            orders.send_msg(Msg::Slideshow(slideshow::Msg::SetAuth(auth.clone())));
-            orders.send_msg(Msg::MySharings(my_sharings::Msg::SetAuth(auth)));
+        Msg::SessionExpired => {
+            if Session::is_started() {
+                end_session(orders);
+                orders.notify(Notif {
+                    notif_type: TypeNotifs::Error,
+                    message: "Session expired, please sign in again".to_string(),
+                });
+                let url = Url::new().add_path_part(LK_LOGIN);
+                orders.notify(subs::UrlRequested::new(url));
+            }
         }
         Msg::MySharings(msg) => {
             my_sharings::update(
@@ -172,6 +227,21 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     }
 }
 
+fn end_session(orders: &mut impl Orders<Msg>) {
+    Session::end();
+    orders.send_msg(Msg::Header(header::Msg::SetIsLogged(false)));
+}
+
+const fn is_editing(page: &Page) -> bool {
+    matches!(page, Page::NewAlbum | Page::EditAlbum)
+}
+
+fn confirm_leave() -> bool {
+    window()
+        .confirm_with_message("You have unsaved changes. Leave anyway?")
+        .unwrap_or(true)
+}
+
 fn init_comp(page: &Page, opt_id: Option<String>, orders: &mut impl Orders<Msg>) {
     match page {
         models::page::Page::MyAlbums => {
@@ -219,6 +289,15 @@ fn view(model: &Model) -> Node<Msg> {
     } else {
         ""
     };
+    if !model.is_config_loaded {
+        return div![
+            notification::view(&model.notification).map_msg(Msg::Notification),
+            div![
+                C!["icon", "spiner", "has-text-primary"],
+                i![C!("ion-load-c")]
+            ],
+        ];
+    }
     div![
         notification::view(&model.notification).map_msg(Msg::Notification),
         header::view(&model.header).map_msg(Msg::Header),
@@ -230,7 +309,7 @@ fn view(model: &Model) -> Node<Msg> {
             ],
             models::page::Page::ShareSlide =>
                 div![slideshow::view(&model.slideshow).map_msg(Msg::Slideshow)],
-            _ => match &model.is_logged {
+            _ => match Session::is_started() {
                 true => {
                     div![
                         C!(c_columns),
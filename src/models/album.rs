@@ -1,19 +1,29 @@
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use super::{
     caption::{Color, Style},
     group::Group,
+    picture::Picture,
     state::State,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Album {
-    #[serde(rename(deserialize = "_id"))]
+    #[serde(rename(deserialize = "_id"), alias = "id")]
     pub id: String,
     pub title: String,
     pub caption_style: Style,
     pub caption_color: Color,
     pub groups: Option<Vec<Group>>,
+    // Asset id of the cover picture
+    #[serde(default)]
+    pub cover: Option<String>,
+    // Incremented on each save, a save based on an older version is rejected
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub updated_at: Option<String>,
     #[serde(skip_serializing, skip_deserializing)]
     pub state: Option<State>,
 }
@@ -26,7 +36,236 @@ impl Album {
             caption_style: Style::Round,
             caption_color: Color::Black,
             groups: Some(Vec::new()),
+            cover: None,
+            version: 0,
+            updated_at: None,
             state: None,
         }
     }
+
+    // New album with the same content and fresh group ids, saved under a new id
+    pub fn duplicate(&self) -> Self {
+        let mut album = self.clone();
+        album.id = String::new();
+        album.title = format!("{} (copy)", self.title);
+        album.version = 0;
+        album.updated_at = None;
+        album.state = None;
+        for group in album.groups.iter_mut().flatten() {
+            group.id = Uuid::new_v4();
+            group.uploads.clear();
+            group.state = None;
+        }
+        album
+    }
+
+    pub fn public_ids(&self) -> Vec<String> {
+        self.groups()
+            .iter()
+            .filter_map(|group| group.pictures.as_ref())
+            .flatten()
+            .map(|picture| picture.public_id.clone())
+            .collect()
+    }
+
+    pub fn pictures_count(&self) -> usize {
+        self.groups()
+            .iter()
+            .filter_map(|group| group.pictures.as_ref())
+            .map(Vec::len)
+            .sum()
+    }
+
+    // The chosen cover, or the first picture when none or when it was removed
+    pub fn cover_picture(&self) -> Option<&Picture> {
+        let mut pictures = self
+            .groups()
+            .iter()
+            .filter_map(|group| group.pictures.as_ref())
+            .flatten();
+        self.cover
+            .as_ref()
+            .and_then(|cover| pictures.clone().find(|p| &p.asset_id == cover))
+            .or_else(|| pictures.next())
+    }
+
+    fn groups(&self) -> &[Group] {
+        self.groups.as_deref().unwrap_or_default()
+    }
+
+    fn group(&self, group: &Group) -> Option<&Group> {
+        self.groups().iter().find(|g| g.id == group.id)
+    }
+
+    // Changes of this album compared to another one, to be displayed
+    pub fn changes(&self, other: &Self) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.title != other.title {
+            changes.push(format!("Album renamed to \"{}\"", self.title));
+        }
+        if self.caption_style != other.caption_style || self.caption_color != other.caption_color {
+            changes.push("Caption style changed".to_string());
+        }
+        if self.cover != other.cover {
+            changes.push("Cover changed".to_string());
+        }
+        for group in self.groups() {
+            match other.group(group) {
+                None => changes.push(format!("Group \"{}\" added", group.title)),
+                Some(other_group) if !same(group, other_group) => {
+                    changes.push(format!("Group \"{}\" changed", group.title));
+                }
+                Some(_) => (),
+            }
+        }
+        for group in other.groups() {
+            if self.group(group).is_none() {
+                changes.push(format!("Group \"{}\" removed", group.title));
+            }
+        }
+        changes
+    }
+
+    // Three-way merge by group: the local changes since the base are applied on the remote album.
+    // The titles of the groups changed locally but removed remotely are returned, these groups are kept
+    pub fn merge(base: &Self, local: &Self, remote: &Self) -> (Self, Vec<String>) {
+        let mut merged = remote.clone();
+        if local.title != base.title {
+            merged.title = local.title.clone();
+        }
+        if local.caption_style != base.caption_style {
+            merged.caption_style = local.caption_style.clone();
+        }
+        if local.caption_color != base.caption_color {
+            merged.caption_color = local.caption_color.clone();
+        }
+        if local.cover != base.cover {
+            merged.cover = local.cover.clone();
+        }
+
+        let mut groups: Vec<Group> = Vec::new();
+        for remote_group in remote.groups() {
+            let base_group = base.group(remote_group);
+            match (local.group(remote_group), base_group) {
+                // Changed locally
+                (Some(local_group), Some(base_group)) if !same(local_group, base_group) => {
+                    groups.push(local_group.clone());
+                }
+                // Removed locally and not changed remotely
+                (None, Some(base_group)) if same(remote_group, base_group) => (),
+                _ => groups.push(remote_group.clone()),
+            }
+        }
+        let mut removed_remotely = Vec::new();
+        for local_group in local.groups() {
+            match (base.group(local_group), remote.group(local_group)) {
+                // Added locally
+                (None, None) => groups.push(local_group.clone()),
+                (Some(base_group), None) if !same(local_group, base_group) => {
+                    removed_remotely.push(local_group.title.clone());
+                    groups.push(local_group.clone());
+                }
+                _ => (),
+            }
+        }
+        merged.groups = Some(groups);
+        (merged, removed_remotely)
+    }
+}
+
+// Groups have no equality, their saved fields are compared
+fn same(group: &Group, other: &Group) -> bool {
+    serde_json::to_string(group).ok() == serde_json::to_string(other).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::*;
+
+    use super::Album;
+    use crate::models::{
+        caption::{Color, Style},
+        group::Group,
+    };
+
+    fn group(title: &str) -> Group {
+        Group {
+            title: title.to_string(),
+            ..Group::new()
+        }
+    }
+
+    fn renamed(group: &Group, title: &str) -> Group {
+        Group {
+            title: title.to_string(),
+            ..group.clone()
+        }
+    }
+
+    fn album(groups: &[&Group]) -> Album {
+        Album {
+            title: "Rome".to_string(),
+            groups: Some(groups.iter().map(|&group| group.clone()).collect()),
+            ..Album::new()
+        }
+    }
+
+    fn titles(album: &Album) -> Vec<&str> {
+        album
+            .groups
+            .iter()
+            .flatten()
+            .map(|group| group.title.as_str())
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn changes_lists_the_differences() {
+        let (day_1, day_2, day_3) = (group("Day 1"), group("Day 2"), group("Day 3"));
+        let old = album(&[&day_1, &day_2]);
+        let mut new = album(&[&renamed(&day_1, "Colosseum"), &day_3]);
+        new.title = "Roma".to_string();
+        new.caption_style = Style::Square;
+
+        assert_eq!(
+            new.changes(&old),
+            [
+                "Album renamed to \"Roma\"",
+                "Caption style changed",
+                "Group \"Colosseum\" changed",
+                "Group \"Day 3\" added",
+                "Group \"Day 2\" removed",
+            ]
+        );
+        assert!(old.changes(&old.clone()).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn merge_applies_the_local_changes_on_the_remote_album() {
+        let (day_1, day_2, day_3) = (group("Day 1"), group("Day 2"), group("Day 3"));
+        let (local_day, remote_day) = (group("Day 4"), group("Day 5"));
+        let base = album(&[&day_1, &day_2, &day_3]);
+        let mut local = album(&[&renamed(&day_1, "Colosseum"), &day_2, &local_day]);
+        local.title = "Roma".to_string();
+        let mut remote = album(&[&day_1, &renamed(&day_2, "Vatican"), &day_3, &remote_day]);
+        remote.caption_color = Color::Red;
+
+        let (merged, removed_remotely) = Album::merge(&base, &local, &remote);
+        assert_eq!(merged.title, "Roma");
+        assert_eq!(merged.caption_color, Color::Red);
+        assert_eq!(titles(&merged), ["Colosseum", "Vatican", "Day 5", "Day 4"]);
+        assert!(removed_remotely.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn merge_keeps_the_changes_of_a_group_removed_on_the_other_side() {
+        let (day_1, day_2) = (group("Day 1"), group("Day 2"));
+        let base = album(&[&day_1, &day_2]);
+        let local = album(&[&renamed(&day_1, "Colosseum")]);
+        let remote = album(&[&renamed(&day_2, "Vatican")]);
+
+        let (merged, removed_remotely) = Album::merge(&base, &local, &remote);
+        assert_eq!(titles(&merged), ["Vatican", "Colosseum"]);
+        assert_eq!(removed_remotely, ["Colosseum"]);
+    }
 }
@@ -0,0 +1,293 @@
+use seed::browser::{Url, UrlSearch};
+
+use super::{album::Album, sharing::Sharing};
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum AlbumSort {
+    Title,
+    Modified,
+    Pictures,
+}
+
+pub const ALBUM_SORT: [AlbumSort; 3] = [AlbumSort::Title, AlbumSort::Modified, AlbumSort::Pictures];
+
+impl AlbumSort {
+    // Value in the url
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Modified => "modified",
+            Self::Pictures => "pictures",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        ALBUM_SORT.iter().copied().find(|sort| sort.key() == key)
+    }
+}
+
+impl std::fmt::Display for AlbumSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let label = match self {
+            Self::Title => "Title",
+            Self::Modified => "Last modified",
+            Self::Pictures => "Number of pictures",
+        };
+        write!(f, "{label}")
+    }
+}
+
+// Search, filters and sort of My Albums, kept in the url query
+#[derive(Clone, Default)]
+pub struct AlbumFilter {
+    pub query: String,
+    pub sort: Option<AlbumSort>,
+    pub shared: Option<bool>,
+    pub with_trips: bool,
+}
+
+impl AlbumFilter {
+    pub fn from_url(url: &Url) -> Self {
+        let value = |key: &str| {
+            url.search()
+                .get(key)
+                .and_then(|values| values.first())
+                .cloned()
+        };
+        Self {
+            query: value("q").unwrap_or_default(),
+            sort: value("sort").as_deref().and_then(AlbumSort::from_key),
+            shared: match value("shared").as_deref() {
+                Some("yes") => Some(true),
+                Some("no") => Some(false),
+                _ => None,
+            },
+            with_trips: value("trips").is_some(),
+        }
+    }
+
+    // Also the query of the albums endpoint
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if !self.query.is_empty() {
+            params.push(("q", self.query.clone()));
+        }
+        if let Some(sort) = self.sort {
+            params.push(("sort", sort.key().to_string()));
+        }
+        if let Some(shared) = self.shared {
+            params.push(("shared", if shared { "yes" } else { "no" }.to_string()));
+        }
+        if self.with_trips {
+            params.push(("trips", "1".to_string()));
+        }
+        params
+    }
+
+    pub fn to_search(&self) -> UrlSearch {
+        let mut search = UrlSearch::default();
+        for (key, value) in self.params() {
+            search.push_value(key, value);
+        }
+        search
+    }
+
+    pub fn apply<'a>(&self, albums: &'a [Album], sharings: &[Sharing]) -> Vec<&'a Album> {
+        let query = self.query.trim().to_lowercase();
+        let mut albums: Vec<&Album> = albums
+            .iter()
+            .filter(|album| query.is_empty() || matches(album, &query))
+            .filter(|album| {
+                self.shared.map_or(true, |shared| {
+                    shared == sharings.iter().any(|s| s.album_id == album.id)
+                })
+            })
+            .filter(|album| !self.with_trips || has_trips(album))
+            .collect();
+        match self.sort {
+            Some(AlbumSort::Title) => albums.sort_by_cached_key(|album| album.title.to_lowercase()),
+            // Most recent first, never saved last
+            Some(AlbumSort::Modified) => {
+                albums.sort_by_cached_key(|album| std::cmp::Reverse(album.updated_at.clone()));
+            }
+            Some(AlbumSort::Pictures) => {
+                albums.sort_by_key(|album| std::cmp::Reverse(album.pictures_count()));
+            }
+            None => (),
+        }
+        albums
+    }
+}
+
+// Title, group titles, captions and trips
+fn matches(album: &Album, query: &str) -> bool {
+    let contains = |text: &str| text.to_lowercase().contains(query);
+    contains(&album.title)
+        || album.groups.iter().flatten().any(|group| {
+            contains(&group.title)
+                || group.trip.as_ref().map_or(false, |trip| {
+                    contains(&trip.origin) || contains(&trip.destination)
+                })
+                || group
+                    .pictures
+                    .iter()
+                    .flatten()
+                    .filter_map(|picture| picture.caption.as_deref())
+                    .any(contains)
+        })
+}
+
+fn has_trips(album: &Album) -> bool {
+    album
+        .groups
+        .iter()
+        .flatten()
+        .any(|group| group.trip.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use seed::browser::Url;
+    use wasm_bindgen_test::*;
+
+    use super::{AlbumFilter, AlbumSort};
+    use crate::models::{
+        album::Album,
+        group::Group,
+        picture::Picture,
+        sharing::Sharing,
+        trip::{TranspMode, Trip},
+    };
+
+    fn album(id: &str, title: &str, updated_at: Option<&str>, pictures: usize) -> Album {
+        let mut group = Group::new();
+        group.pictures = Some(vec![Picture::default(); pictures]);
+        Album {
+            id: id.to_string(),
+            title: title.to_string(),
+            groups: Some(vec![group]),
+            updated_at: updated_at.map(str::to_string),
+            ..Album::new()
+        }
+    }
+
+    fn albums() -> Vec<Album> {
+        let mut venice = album("1", "Venice", Some("2023-05-01T10:00:00Z"), 3);
+        if let Some(group) = venice.groups.iter_mut().flatten().next() {
+            group.trip = Some(Trip {
+                transp_mode: TranspMode::Train,
+                origin: "Milan".to_string(),
+                destination: "Venice".to_string(),
+            });
+        }
+        let mut rome = album("2", "rome", None, 1);
+        if let Some(picture) = rome
+            .groups
+            .iter_mut()
+            .flatten()
+            .flat_map(|group| group.pictures.iter_mut().flatten())
+            .next()
+        {
+            picture.caption = Some("Trevi fountain".to_string());
+        }
+        vec![
+            venice,
+            rome,
+            album("3", "Alps", Some("2023-08-01T10:00:00Z"), 5),
+        ]
+    }
+
+    fn sharings() -> Vec<Sharing> {
+        vec![Sharing {
+            id: "s".to_string(),
+            album_id: "2".to_string(),
+            album_name: "rome".to_string(),
+            nb_like: 0,
+            nb_view: 0,
+        }]
+    }
+
+    fn ids(filter: &AlbumFilter) -> Vec<String> {
+        filter
+            .apply(&albums(), &sharings())
+            .into_iter()
+            .map(|album| album.id.clone())
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn from_url_reads_the_query() {
+        let url = Url::from_str("/?q=rome&sort=modified&shared=no&trips=1").unwrap();
+        let filter = AlbumFilter::from_url(&url);
+        assert_eq!(filter.query, "rome");
+        assert!(filter.sort == Some(AlbumSort::Modified));
+        assert_eq!(filter.shared, Some(false));
+        assert!(filter.with_trips);
+        assert_eq!(
+            filter.params(),
+            [
+                ("q", "rome".to_string()),
+                ("sort", "modified".to_string()),
+                ("shared", "no".to_string()),
+                ("trips", "1".to_string()),
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn from_url_ignores_the_unknown_values() {
+        let url = Url::from_str("/?sort=size&shared=maybe").unwrap();
+        let filter = AlbumFilter::from_url(&url);
+        assert!(filter.query.is_empty());
+        assert!(filter.sort.is_none());
+        assert_eq!(filter.shared, None);
+        assert!(!filter.with_trips);
+    }
+
+    #[wasm_bindgen_test]
+    fn apply_searches_titles_trips_and_captions() {
+        let search = |query: &str| {
+            ids(&AlbumFilter {
+                query: query.to_string(),
+                ..AlbumFilter::default()
+            })
+        };
+        assert_eq!(search(" ROME "), ["2"]);
+        assert_eq!(search("milan"), ["1"]);
+        assert_eq!(search("fountain"), ["2"]);
+        assert!(search("paris").is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    fn apply_filters_shared_and_trips() {
+        let shared = |shared| {
+            ids(&AlbumFilter {
+                shared: Some(shared),
+                ..AlbumFilter::default()
+            })
+        };
+        assert_eq!(shared(true), ["2"]);
+        assert_eq!(shared(false), ["1", "3"]);
+        let with_trips = AlbumFilter {
+            with_trips: true,
+            ..AlbumFilter::default()
+        };
+        assert_eq!(ids(&with_trips), ["1"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn apply_sorts() {
+        let sorted = |sort| {
+            ids(&AlbumFilter {
+                sort: Some(sort),
+                ..AlbumFilter::default()
+            })
+        };
+        assert_eq!(sorted(AlbumSort::Title), ["3", "2", "1"]);
+        assert_eq!(sorted(AlbumSort::Modified), ["3", "1", "2"]);
+        assert_eq!(sorted(AlbumSort::Pictures), ["3", "1", "2"]);
+        assert_eq!(ids(&AlbumFilter::default()), ["1", "2", "3"]);
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use super::{album::Album, picture::Picture, state::State};
+
+// Album of the list pages, without its groups
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AlbumSummary {
+    #[serde(rename(deserialize = "_id"), alias = "id")]
+    pub id: String,
+    pub title: String,
+    pub cover: Option<Picture>,
+    pub groups_count: usize,
+    pub pictures_count: usize,
+    #[serde(default)]
+    pub shares_count: usize,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub state: Option<State>,
+}
+
+impl AlbumSummary {
+    pub fn new(album: &Album, shares_count: usize) -> Self {
+        Self {
+            id: album.id.clone(),
+            title: album.title.clone(),
+            cover: album.cover_picture().cloned(),
+            groups_count: album.groups.as_ref().map_or(0, Vec::len),
+            pictures_count: album.pictures_count(),
+            shares_count,
+            updated_at: album.updated_at.clone(),
+            state: None,
+        }
+    }
+
+    // Date part of the last save
+    pub fn updated_on(&self) -> Option<&str> {
+        self.updated_at
+            .as_deref()
+            .and_then(|updated_at| updated_at.get(..10))
+    }
+}
@@ -0,0 +1,78 @@
+use std::cell::RefCell;
+
+use serde::Deserialize;
+
+use super::vars::{CLD_API_URI, CLD_RES_URI};
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Config {
+    pub base_uri: String,
+    pub cloud_name: String,
+    pub upload_preset: String,
+    #[serde(default)]
+    pub local_backend: bool,
+    #[serde(default)]
+    pub resize: Option<Resize>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Jpeg,
+    Webp,
+}
+
+impl ImageFormat {
+    pub const fn mime(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+// Pictures are downsized in the browser before the upload
+#[derive(Deserialize, Debug, Clone)]
+pub struct Resize {
+    pub max_dimension: u32,
+    pub format: ImageFormat,
+    pub quality: f64,
+}
+
+impl Config {
+    pub fn upload_uri(&self) -> String {
+        format!("{CLD_API_URI}{}/image/upload/", self.cloud_name)
+    }
+
+    pub fn img_uri(&self) -> String {
+        format!("{CLD_RES_URI}{}/image/upload/", self.cloud_name)
+    }
+
+    pub fn thumb_uri(&self) -> String {
+        self.img_uri() + "w_128,h_128,c_fill/"
+    }
+
+    pub fn very_low_uri(&self) -> String {
+        self.img_uri() + "q_auto:low,w_5,h_5/"
+    }
+}
+
+// Loaded once from config.json at startup
+thread_local! {
+    static CONFIG: RefCell<Config> = RefCell::new(Config::default());
+}
+
+pub fn set(config: Config) {
+    CONFIG.with(|c| *c.borrow_mut() = config);
+}
+
+pub fn get() -> Config {
+    CONFIG.with(|c| c.borrow().clone())
+}
@@ -0,0 +1,79 @@
+use seed::{
+    browser::web_storage::{LocalStorage, WebStorage},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::album::Album;
+
+const DRAFT_KEY: &str = "amaker_draft_";
+// Albums not saved yet get a generated id starting with it, so each editor keeps its own draft
+const NEW_ALBUM_ID: &str = "new";
+
+// Last state of an album being edited, kept until it is saved
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Draft {
+    // The album id, or the generated one of a new album
+    #[serde(skip)]
+    pub id: String,
+    pub album: Album,
+    pub saved_at: f64,
+}
+
+impl Draft {
+    pub fn new(id: &str, album: Album) -> Self {
+        Self {
+            id: id.to_string(),
+            album,
+            saved_at: js_sys::Date::now(),
+        }
+    }
+
+    pub fn new_id() -> String {
+        format!("{NEW_ALBUM_ID}_{}", Uuid::new_v4())
+    }
+
+    fn key(id: &str) -> String {
+        format!("{DRAFT_KEY}{id}")
+    }
+
+    pub fn load(id: &str) -> Option<Self> {
+        let draft: Self = LocalStorage::get(Self::key(id)).ok()?;
+        Some(Self {
+            id: id.to_string(),
+            ..draft
+        })
+    }
+
+    // The most recent draft of a new album, to restore in a new editor
+    pub fn load_new() -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .filter(|draft| draft.id.starts_with(NEW_ALBUM_ID))
+            .max_by(|a, b| a.saved_at.total_cmp(&b.saved_at))
+    }
+
+    // Drafts of all the albums, left by any editor
+    pub fn all() -> Vec<Self> {
+        let len = LocalStorage::len().unwrap_or_default();
+        (0..len)
+            .filter_map(|index| LocalStorage::key(index).ok())
+            .filter_map(|key| key.strip_prefix(DRAFT_KEY).and_then(Self::load))
+            .collect()
+    }
+
+    pub fn save(&self) {
+        _ = LocalStorage::insert(Self::key(&self.id), self);
+    }
+
+    pub fn clear(id: &str) {
+        _ = LocalStorage::remove(Self::key(id));
+    }
+
+    pub fn saved_at_label(&self) -> String {
+        js_sys::Date::new(&self.saved_at.into())
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+}
@@ -1,7 +1,27 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{picture::Picture, state::State, trip::Trip};
+use super::{picture::Picture, state::State, trip::Trip, upload::Upload};
+
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Copy)]
+pub enum SortBy {
+    DateTaken,
+    FileName,
+    Caption,
+}
+
+pub const SORT_BY: [SortBy; 3] = [SortBy::DateTaken, SortBy::FileName, SortBy::Caption];
+
+impl std::fmt::Display for SortBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let label = match self {
+            Self::DateTaken => "Date taken",
+            Self::FileName => "File name",
+            Self::Caption => "Caption",
+        };
+        write!(f, "{label}")
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Group {
@@ -9,8 +29,11 @@ pub struct Group {
     pub title: String,
     pub pictures: Option<Vec<Picture>>,
     pub trip: Option<Trip>,
+    // Pictures are sorted after each upload when set
+    #[serde(default)]
+    pub auto_sort: Option<SortBy>,
     #[serde(skip_serializing, skip_deserializing)]
-    pub count_fake_pictures: u32,
+    pub uploads: Vec<Upload>,
     #[serde(skip_serializing, skip_deserializing)]
     pub state: Option<State>,
 }
@@ -22,8 +45,96 @@ impl Group {
             title: String::new(),
             pictures: Some(Vec::new()),
             trip: None,
-            count_fake_pictures: 0,
+            auto_sort: None,
+            uploads: Vec::new(),
             state: None,
         }
     }
+
+    // Pictures without the value are kept at the end
+    pub fn sort_pictures(&mut self, sort_by: SortBy) {
+        if let Some(pictures) = &mut self.pictures {
+            match sort_by {
+                SortBy::DateTaken => {
+                    pictures.sort_by_cached_key(|p| (p.taken_at.is_none(), p.taken_at.clone()));
+                }
+                SortBy::FileName => pictures.sort_by_cached_key(|p| {
+                    let name = p.original_filename.as_deref().map(str::to_lowercase);
+                    (name.is_none(), name)
+                }),
+                SortBy::Caption => pictures.sort_by_cached_key(|p| {
+                    let caption = p.caption.as_deref().map(str::to_lowercase);
+                    (caption.is_none(), caption)
+                }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::*;
+
+    use super::{Group, SortBy};
+    use crate::models::picture::Picture;
+
+    fn group(pictures: &[(&str, Option<&str>, Option<&str>, Option<&str>)]) -> Group {
+        let mut group = Group::new();
+        group.pictures = Some(
+            pictures
+                .iter()
+                .map(|(asset_id, taken_at, file_name, caption)| Picture {
+                    asset_id: (*asset_id).to_string(),
+                    taken_at: taken_at.map(str::to_string),
+                    original_filename: file_name.map(str::to_string),
+                    caption: caption.map(str::to_string),
+                    ..Picture::default()
+                })
+                .collect(),
+        );
+        group
+    }
+
+    fn order(group: &Group) -> Vec<&str> {
+        group
+            .pictures
+            .iter()
+            .flatten()
+            .map(|picture| picture.asset_id.as_str())
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    fn sort_pictures_by_date_taken() {
+        let mut group = group(&[
+            ("a", None, None, None),
+            ("b", Some("2023-07-14T18:30:05"), None, None),
+            ("c", Some("2022-01-01T08:00:00"), None, None),
+        ]);
+        group.sort_pictures(SortBy::DateTaken);
+        assert_eq!(order(&group), ["c", "b", "a"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn sort_pictures_by_file_name_ignores_the_case() {
+        let mut group = group(&[
+            ("a", None, Some("IMG_2"), None),
+            ("b", None, None, None),
+            ("c", None, Some("img_1"), None),
+        ]);
+        group.sort_pictures(SortBy::FileName);
+        assert_eq!(order(&group), ["c", "a", "b"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn sort_pictures_by_caption_keeps_the_equal_ones_in_order() {
+        let mut group = group(&[
+            ("a", None, None, Some("Beach")),
+            ("b", None, None, None),
+            ("c", None, None, Some("apple")),
+            ("d", None, None, Some("beach")),
+        ]);
+        group.sort_pictures(SortBy::Caption);
+        assert_eq!(order(&group), ["c", "a", "d", "b"]);
+    }
 }
@@ -1,26 +1,40 @@
-use super::{picture::Picture, state::DeleteStatus, trip::Trip};
+use super::{
+    group::SortBy,
+    picture::Picture,
+    state::DeleteStatus,
+    trip::Trip,
+    upload::{Upload, UploadStatus},
+};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum UpdateType {
+    #[default]
     Title,
-    CountFakePictures,
+    AddUploads,
+    UploadStatus,
+    RemoveUpload,
+    ReplaceUpload,
     AddPicture,
-    DeletePicture,
     Caption,
     DeleteState,
     TripChanged,
+    Sort,
+    AutoSort,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GroupUpdate {
     pub upd_type: UpdateType,
     pub id: Uuid,
     pub grp_data: Option<String>,
     pub picture: Option<Picture>,
-    pub count_fake_pictures: Option<u32>,
+    pub uploads: Option<Vec<Upload>>,
+    pub upload_id: Option<Uuid>,
+    pub upload_status: Option<UploadStatus>,
     pub asset_id: Option<String>,
     pub caption: Option<String>,
     pub delete_status: Option<DeleteStatus>,
     pub trip: Option<Trip>,
+    pub sort_by: Option<SortBy>,
 }
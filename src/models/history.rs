@@ -0,0 +1,143 @@
+use super::album::Album;
+
+const MAX_STEPS: usize = 100;
+
+// Undo and redo stacks of album snapshots
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Album>,
+    redo: Vec<Album>,
+    last_edit: Option<String>,
+}
+
+impl History {
+    pub const fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+        }
+    }
+
+    // Consecutive changes of the same text field are a single step
+    pub fn record(&mut self, album: &Album, edit: Option<String>) {
+        if edit.is_some() && edit == self.last_edit {
+            return;
+        }
+        self.undo.push(album.clone());
+        if self.undo.len() > MAX_STEPS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        self.last_edit = edit;
+    }
+
+    pub fn undo(&mut self, current: &Album) -> Option<Album> {
+        let album = self.undo.pop()?;
+        self.redo.push(current.clone());
+        self.last_edit = None;
+        Some(album)
+    }
+
+    pub fn redo(&mut self, current: &Album) -> Option<Album> {
+        let album = self.redo.pop()?;
+        self.undo.push(current.clone());
+        self.last_edit = None;
+        Some(album)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_edit = None;
+    }
+
+    pub fn snapshots_mut(&mut self) -> impl Iterator<Item = &mut Album> {
+        self.undo.iter_mut().chain(self.redo.iter_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::*;
+
+    use super::{History, MAX_STEPS};
+    use crate::models::album::Album;
+
+    fn album(title: &str) -> Album {
+        Album {
+            title: title.to_string(),
+            ..Album::new()
+        }
+    }
+
+    fn title(album: Option<Album>) -> Option<String> {
+        album.map(|album| album.title)
+    }
+
+    #[wasm_bindgen_test]
+    fn undo_and_redo_walk_the_snapshots() {
+        let mut history = History::new();
+        assert!(!history.can_undo());
+        history.record(&album("a"), None);
+        history.record(&album("b"), None);
+
+        assert_eq!(title(history.undo(&album("c"))).as_deref(), Some("b"));
+        assert_eq!(title(history.undo(&album("b"))).as_deref(), Some("a"));
+        assert!(!history.can_undo());
+        assert_eq!(title(history.undo(&album("a"))), None);
+
+        assert_eq!(title(history.redo(&album("a"))).as_deref(), Some("b"));
+        assert_eq!(title(history.redo(&album("b"))).as_deref(), Some("c"));
+        assert!(!history.can_redo());
+    }
+
+    #[wasm_bindgen_test]
+    fn record_clears_the_redo() {
+        let mut history = History::new();
+        history.record(&album("a"), None);
+        history.undo(&album("b"));
+        assert!(history.can_redo());
+
+        history.record(&album("a"), None);
+        assert!(!history.can_redo());
+    }
+
+    #[wasm_bindgen_test]
+    fn record_merges_the_same_text_edit() {
+        let mut history = History::new();
+        history.record(&album("a"), Some("title".to_string()));
+        history.record(&album("ab"), Some("title".to_string()));
+        history.record(&album("abc"), Some("caption".to_string()));
+
+        assert_eq!(title(history.undo(&album("abcd"))).as_deref(), Some("abc"));
+        assert_eq!(title(history.undo(&album("abc"))).as_deref(), Some("a"));
+        assert!(!history.can_undo());
+
+        // An undo ends the edit
+        history.record(&album("a"), Some("title".to_string()));
+        history.record(&album("ab"), Some("title".to_string()));
+        assert_eq!(title(history.undo(&album("abc"))).as_deref(), Some("a"));
+    }
+
+    #[wasm_bindgen_test]
+    fn record_drops_the_oldest_steps() {
+        let mut history = History::new();
+        for step in 0..=MAX_STEPS {
+            history.record(&album(&step.to_string()), None);
+        }
+        let mut last = None;
+        while let Some(album) = history.undo(&album("current")) {
+            last = Some(album);
+        }
+        assert_eq!(title(last).as_deref(), Some("1"));
+    }
+}
@@ -1,11 +1,19 @@
 pub mod album;
+pub mod album_filter;
+pub mod album_summary;
 pub mod caption;
+pub mod config;
+pub mod draft;
 pub mod group;
 pub mod group_update;
+pub mod history;
 pub mod notif;
 pub mod page;
+pub mod paged;
 pub mod picture;
+pub mod session;
 pub mod sharing;
 pub mod state;
 pub mod trip;
+pub mod upload;
 pub mod vars;
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+pub const PAGE_SIZE: usize = 20;
+
+// One page of a list, the cursor is given back to get the next one
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Paged<T> {
+    // The cursor is the offset of the page, as the local backend does
+    pub fn from_offset(items: Vec<T>, cursor: Option<&str>) -> Self {
+        let offset = cursor.and_then(|c| c.parse().ok()).unwrap_or(0);
+        let len = items.len();
+        let next = offset + PAGE_SIZE;
+        Self {
+            items: items.into_iter().skip(offset).take(PAGE_SIZE).collect(),
+            next_cursor: (next < len).then(|| next.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::*;
+
+    use super::{Paged, PAGE_SIZE};
+
+    fn page(len: usize, cursor: Option<&str>) -> Paged<usize> {
+        Paged::from_offset((0..len).collect(), cursor)
+    }
+
+    #[wasm_bindgen_test]
+    fn from_offset_gives_the_next_cursor() {
+        let first = page(PAGE_SIZE * 2 + 5, None);
+        assert_eq!(first.items, (0..PAGE_SIZE).collect::<Vec<_>>());
+        assert_eq!(first.next_cursor, Some(PAGE_SIZE.to_string()));
+
+        let last = page(PAGE_SIZE * 2 + 5, Some(&(PAGE_SIZE * 2).to_string()));
+        assert_eq!(
+            last.items,
+            (PAGE_SIZE * 2..PAGE_SIZE * 2 + 5).collect::<Vec<_>>()
+        );
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn from_offset_ends_on_a_full_page() {
+        let last = page(PAGE_SIZE * 2, Some(&PAGE_SIZE.to_string()));
+        assert_eq!(last.items.len(), PAGE_SIZE);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[wasm_bindgen_test]
+    fn from_offset_handles_empty_and_invalid_cursors() {
+        let empty = page(0, None);
+        assert!(empty.items.is_empty());
+        assert_eq!(empty.next_cursor, None);
+
+        let invalid = page(5, Some("abc"));
+        assert_eq!(invalid.items, [0, 1, 2, 3, 4]);
+
+        let past_the_end = page(5, Some("40"));
+        assert!(past_the_end.items.is_empty());
+        assert_eq!(past_the_end.next_cursor, None);
+    }
+}
@@ -1,9 +1,58 @@
 use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Gps {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Gps {
+    // Haversine formula
+    pub fn distance_km(&self, other: &Self) -> f64 {
+        const EARTH_RADIUS_KM: f64 = 6371.;
+        let d_lat = (other.lat - self.lat).to_radians();
+        let d_lon = (other.lon - self.lon).to_radians();
+        let a = (d_lat / 2.).sin().powi(2)
+            + self.lat.to_radians().cos()
+                * other.lat.to_radians().cos()
+                * (d_lon / 2.).sin().powi(2);
+        2. * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Picture {
     pub asset_id: String,
     pub public_id: String,
     pub format: String,
     pub caption: Option<String>,
+    // Returned by Cloudinary, without the extension
+    #[serde(default)]
+    pub original_filename: Option<String>,
+    // Read from the EXIF of the original file, formatted as YYYY-MM-DDTHH:MM:SS
+    #[serde(default)]
+    pub taken_at: Option<String>,
+    #[serde(default)]
+    pub gps: Option<Gps>,
+    #[serde(default)]
+    pub camera: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    // Returned by Cloudinary, the storage cleanup keeps the recent uploads
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+impl Picture {
+    // Date taken and camera, shown as a tooltip
+    pub fn details(&self) -> String {
+        let date = self.taken_at.as_deref().map(|date| date.replace('T', " "));
+        [date, self.camera.clone()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" - ")
+    }
 }
@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+
+use seed::browser::web_storage::{LocalStorage, WebStorage};
+use serde::{Deserialize, Serialize};
+
+const SESSION_KEY: &str = "amaker_session";
+
+// Set while signed in, the api reads the token from it on each call
+thread_local! {
+    static CURRENT: RefCell<Option<Session>> = RefCell::new(None);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Session {
+    pub token: String,
+}
+
+impl Session {
+    pub const fn new(token: String) -> Self {
+        Self { token }
+    }
+
+    pub fn load() -> Option<Self> {
+        LocalStorage::get(SESSION_KEY).ok()
+    }
+
+    // Kept for the next visit
+    pub fn start(self) {
+        _ = LocalStorage::insert(SESSION_KEY, &self);
+        CURRENT.with(|current| *current.borrow_mut() = Some(self));
+    }
+
+    pub fn end() {
+        _ = LocalStorage::remove(SESSION_KEY);
+        CURRENT.with(|current| *current.borrow_mut() = None);
+    }
+
+    pub fn is_started() -> bool {
+        CURRENT.with(|current| current.borrow().is_some())
+    }
+
+    pub fn auth_header(&self) -> String {
+        format!("Bearer {}", self.token)
+    }
+}
+
+// Empty when signed out
+pub fn auth_header() -> String {
+    CURRENT.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .map(Session::auth_header)
+            .unwrap_or_default()
+    })
+}
+
+// Notified by the components when an API call returns 401
+pub struct SessionExpired;
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Sharing {
-    #[serde(rename(deserialize = "_id"))]
+    #[serde(rename(deserialize = "_id"), alias = "id")]
     pub id: String,
     pub album_id: String,
     pub album_name: String,
@@ -3,6 +3,7 @@ pub enum DeleteStatus {
     #[default]
     AskDelete,
     Deleting,
+    Moving,
 }
 
 #[derive(Debug, Clone, Default)]
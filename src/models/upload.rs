@@ -0,0 +1,44 @@
+use uuid::Uuid;
+use web_sys::{AbortController, File};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum UploadStatus {
+    Queued,
+    Processing,
+    Uploading(u32),
+    Failed,
+    Done,
+}
+
+#[derive(Debug, Clone)]
+pub struct Upload {
+    pub id: Uuid,
+    pub file: File,
+    pub status: UploadStatus,
+    pub attempts: u32,
+    pub abort: AbortController,
+}
+
+impl Upload {
+    pub fn new(file: File) -> Option<Self> {
+        let abort = AbortController::new().ok()?;
+        Some(Self {
+            id: Uuid::new_v4(),
+            file,
+            status: UploadStatus::Queued,
+            attempts: 0,
+            abort,
+        })
+    }
+
+    pub const fn is_pending(&self) -> bool {
+        !matches!(self.status, UploadStatus::Done)
+    }
+
+    pub const fn is_cancellable(&self) -> bool {
+        matches!(
+            self.status,
+            UploadStatus::Queued | UploadStatus::Processing | UploadStatus::Uploading(_)
+        )
+    }
+}
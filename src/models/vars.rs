@@ -1,8 +1,4 @@
-pub const BASE_URI: &str = "https://data.mongodb-api.com/app/amaker-hejmf/endpoint/";
-pub const UPLOAD_URI: &str = "https://api.cloudinary.com/v1_1/gatoorg/image/upload/";
-pub const DESTROY_URI: &str = "https://api.cloudinary.com/v1_1/gatoorg/image/destroy";
-pub const THUMB_URI: &str = "https://res.cloudinary.com/gatoorg/image/upload/w_128,h_128,c_fill/";
-pub const IMG_URI: &str = "https://res.cloudinary.com/gatoorg/image/upload/";
-pub const VERY_LOW_URI: &str =
-    "https://res.cloudinary.com/gatoorg/image/upload/q_auto:low,w_5,h_5/";
+pub const CONFIG_URI: &str = "/config.json";
+pub const CLD_API_URI: &str = "https://api.cloudinary.com/v1_1/";
+pub const CLD_RES_URI: &str = "https://res.cloudinary.com/";
 pub const AUTH_HEAD: &str = "Authorization";